@@ -1,10 +1,13 @@
 use crate::agent::Agent;
 use crate::context::Context;
+use crate::dead_letter::{self, DeadLetter, DeadReason};
 use anyhow::{Error, Result};
 use async_trait::async_trait;
 use crb_core::{mpsc, watch};
 use crb_runtime::Stopper;
-use crb_send::{Recipient, Sender};
+use crb_send::{Headers, Recipient, Sender};
+use std::any::{type_name, Any};
+use std::time::SystemTime;
 
 pub struct AddressJoint<A: Agent> {
     msg_rx: mpsc::UnboundedReceiver<Envelope<A>>,
@@ -41,6 +44,11 @@ impl<A: Agent> AddressJoint<A> {
     pub fn close(&mut self) {
         self.msg_rx.close();
     }
+
+    /// Takes a message that is already in the mailbox without waiting.
+    pub fn try_next_envelope(&mut self) -> Option<Envelope<A>> {
+        self.msg_rx.try_recv().ok()
+    }
 }
 
 pub struct Address<A: Agent> {
@@ -50,10 +58,35 @@ pub struct Address<A: Agent> {
 }
 
 impl<A: Agent> Address<A> {
-    pub fn send(&self, msg: impl MessageFor<A>) -> Result<()> {
-        self.msg_tx
-            .send(Box::new(msg))
-            .map_err(|_| Error::msg("Can't send the message to the actor"))
+    pub fn send<M: MessageFor<A>>(&self, msg: M) -> Result<()> {
+        self.msg_tx.send(Box::new(msg)).map_err(|err| {
+            dead_letter::post::<M>(|| {
+                let payload = err.0.as_any();
+                DeadLetter::new::<A, M>(DeadReason::Undeliverable, Some(payload))
+            });
+            Error::msg("Can't send the message to the actor")
+        })
+    }
+
+    /// Sends the message with headers that are available
+    /// from the context of the handler.
+    pub fn send_with<M: MessageFor<A>>(&self, msg: M, mut headers: Headers) -> Result<()> {
+        headers.stamp();
+        let envelope = Box::new(WithHeaders {
+            headers,
+            message: type_name::<M>(),
+            envelope: Box::new(msg),
+        });
+        self.msg_tx.send(envelope).map_err(|err| {
+            dead_letter::post::<M>(|| {
+                let payload = match err.0.as_any().downcast::<WithHeaders<A>>() {
+                    Ok(with_headers) => with_headers.envelope.as_any(),
+                    Err(envelope) => envelope,
+                };
+                DeadLetter::new::<A, M>(DeadReason::Undeliverable, Some(payload))
+            });
+            Error::msg("Can't send the message to the actor")
+        })
     }
 
     /// Important! `join` must use a reference to allow using it under `DerefMut` trait
@@ -85,6 +118,10 @@ where
     fn send(&self, input: M) -> Result<()> {
         Address::send(self, input)
     }
+
+    fn send_with(&self, input: M, headers: Headers) -> Result<()> {
+        Address::send_with(self, input, headers)
+    }
 }
 
 impl<A: Agent> Address<A> {
@@ -111,7 +148,84 @@ impl AgentStatus {
 
 pub type Envelope<A> = Box<dyn MessageFor<A>>;
 
+/// Converts messages to `Any` without relying on trait upcasting.
+///
+/// It's implemented for all types automatically.
+pub trait AsAny: Any + Send {
+    fn as_any(self: Box<Self>) -> Box<dyn Any + Send>;
+
+    fn as_any_ref(&self) -> &dyn Any;
+}
+
+impl<T: Any + Send> AsAny for T {
+    fn as_any(self: Box<Self>) -> Box<dyn Any + Send> {
+        self
+    }
+
+    fn as_any_ref(&self) -> &dyn Any {
+        self
+    }
+}
+
 #[async_trait]
-pub trait MessageFor<A: Agent>: Send + 'static {
+pub trait MessageFor<A: Agent>: AsAny + 'static {
     async fn handle(self: Box<Self>, actor: &mut A, ctx: &mut Context<A>) -> Result<()>;
+
+    /// Control messages are handled even if a state defers or rejects messages.
+    fn is_control(&self) -> bool {
+        false
+    }
+
+    /// The type name of the message used in dead letters.
+    fn message_name(&self) -> &'static str {
+        type_name::<Self>()
+    }
+}
+
+/// An envelope with headers.
+pub(crate) struct WithHeaders<A: Agent> {
+    pub headers: Headers,
+    pub message: &'static str,
+    pub envelope: Envelope<A>,
+}
+
+impl<A: Agent> WithHeaders<A> {
+    /// Drops the message if its deadline has passed, otherwise returns it back.
+    pub(crate) fn check_deadline(self: Box<Self>, ctx: &mut Context<A>) -> Option<Box<Self>> {
+        // Control messages must be handled anyway
+        if !self.headers.is_expired() || self.envelope.is_control() {
+            return Some(self);
+        }
+        ctx.expired += 1;
+        log::trace!("An expired message {} is dropped", self.message);
+        dead_letter::post::<Self>(|| DeadLetter {
+            target: type_name::<A>(),
+            message: self.message,
+            reason: DeadReason::Expired,
+            timestamp: SystemTime::now(),
+            payload: Some(self.envelope.as_any()),
+        });
+        None
+    }
+}
+
+#[async_trait]
+impl<A: Agent> MessageFor<A> for WithHeaders<A> {
+    async fn handle(self: Box<Self>, agent: &mut A, ctx: &mut Context<A>) -> Result<()> {
+        let Some(this) = self.check_deadline(ctx) else {
+            return Ok(());
+        };
+        let outer = ctx.headers.replace(this.headers);
+        let result = this.envelope.handle(agent, ctx).await;
+        ctx.headers = outer;
+        result
+    }
+
+    fn is_control(&self) -> bool {
+        self.envelope.is_control()
+    }
+
+    fn message_name(&self) -> &'static str {
+        self.message
+    }
 }
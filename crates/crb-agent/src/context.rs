@@ -1,10 +1,14 @@
 use crate::address::{Address, AddressJoint, Envelope};
 use crate::agent::Agent;
+use crate::dead_letter::{self, DeadReason};
 use crate::extension::ExtensionFor;
+use crate::performers::nested_performer::NestedStates;
 use crate::performers::Next;
+use crate::stash::Stash;
 use anyhow::{anyhow, Result};
 use async_trait::async_trait;
 use crb_runtime::{Controller, ManagedContext, ReachableContext};
+use crb_send::Headers;
 use derive_more::{Deref, DerefMut};
 use std::any::{Any, TypeId};
 use std::collections::HashMap;
@@ -15,6 +19,9 @@ pub struct Context<A: Agent> {
     #[deref_mut]
     context: A::Context,
     extensions: Option<HashMap<TypeId, Box<dyn Any + Send>>>,
+    pub(crate) states: NestedStates<A>,
+    pub(crate) headers: Option<Headers>,
+    pub(crate) expired: usize,
 }
 
 impl<A: Agent> Context<A> {
@@ -22,9 +29,27 @@ impl<A: Agent> Context<A> {
         Self {
             context,
             extensions: None,
+            states: NestedStates::default(),
+            headers: None,
+            expired: 0,
         }
     }
 
+    /// The number of messages dropped because their deadlines have passed.
+    pub fn expired_messages(&self) -> usize {
+        self.expired
+    }
+
+    /// Headers of the message that is being handled.
+    pub fn headers(&self) -> Option<&Headers> {
+        self.headers.as_ref()
+    }
+
+    /// Active nested states and the history of transitions between them.
+    pub fn states(&self) -> &NestedStates<A> {
+        &self.states
+    }
+
     pub fn add_extension<E>(&mut self, ext: E)
     where
         E: ExtensionFor<A>,
@@ -75,6 +100,7 @@ pub struct AgentSession<A: Agent> {
     pub controller: Controller,
     pub next_state: Option<Next<A>>,
     pub joint: AddressJoint<A>,
+    pub stash: Stash<A>,
     #[deref]
     #[deref_mut]
     pub address: Address<A>,
@@ -88,6 +114,29 @@ impl<A: Agent> AgentSession<A> {
     pub fn do_next(&mut self, next_state: Next<A>) {
         self.next_state = Some(next_state);
     }
+
+    /// Closes the mailbox and sends all stashed and pending messages
+    /// to the dead-letter recipient, since nobody will handle them.
+    pub fn reject_pending(&mut self) {
+        self.take_pending(Err);
+    }
+
+    /// Closes the mailbox and passes all stashed and pending messages to `take`.
+    ///
+    /// Messages returned back by `take` are sent to the dead-letter recipient.
+    pub fn take_pending<F>(&mut self, mut take: F)
+    where
+        F: FnMut(Envelope<A>) -> Result<(), Envelope<A>>,
+    {
+        self.joint.close();
+        let mut pending = self.stash.drain();
+        pending.extend(std::iter::from_fn(|| self.joint.try_next_envelope()));
+        for envelope in pending {
+            if let Err(envelope) = take(envelope) {
+                dead_letter::post_envelope(envelope, DeadReason::Undeliverable);
+            }
+        }
+    }
 }
 
 impl<A: Agent> Default for AgentSession<A> {
@@ -99,6 +148,7 @@ impl<A: Agent> Default for AgentSession<A> {
             controller,
             next_state: None,
             joint,
+            stash: Stash::default(),
             address,
         }
     }
@@ -139,6 +189,9 @@ impl<A: Agent> AgentContext<A> for AgentSession<A> {
     }
 
     async fn next_envelope(&mut self) -> Option<Envelope<A>> {
+        if let Some(envelope) = self.stash.next_unstashed() {
+            return Some(envelope);
+        }
         self.joint().next_envelope().await
     }
 }
@@ -0,0 +1,197 @@
+//! Messages that were sent, but not handled.
+
+use crate::address::{Envelope, WithHeaders};
+use crate::agent::{Agent, Standalone};
+use crate::context::{AgentSession, Context};
+use crate::global::CRB;
+use crate::message::event::{Event, OnEvent};
+use anyhow::Result;
+use async_trait::async_trait;
+use crb_send::Sender;
+use std::any::{type_name, Any, TypeId};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DeadReason {
+    /// The agent is gone and its mailbox is closed.
+    Undeliverable,
+    /// The agent doesn't implement a handler of the message.
+    Unhandled,
+    /// The deadline of the message has passed before it was handled.
+    Expired,
+    /// The agent has rejected the message in its current state.
+    Rejected,
+}
+
+/// A message that was lost.
+pub struct DeadLetter {
+    /// The type name of the agent that should have handled the message.
+    pub target: &'static str,
+    /// The type name of the message.
+    pub message: &'static str,
+    pub reason: DeadReason,
+    pub timestamp: SystemTime,
+    /// The message if it was available.
+    pub payload: Option<Box<dyn Any + Send>>,
+}
+
+impl fmt::Debug for DeadLetter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DeadLetter")
+            .field("target", &self.target)
+            .field("message", &self.message)
+            .field("reason", &self.reason)
+            .field("timestamp", &self.timestamp)
+            .finish()
+    }
+}
+
+impl DeadLetter {
+    pub fn new<A, M>(reason: DeadReason, payload: Option<Box<dyn Any + Send>>) -> Self
+    where
+        A: Agent,
+        M: 'static,
+    {
+        Self {
+            target: type_name::<A>(),
+            message: type_name::<M>(),
+            reason,
+            timestamp: SystemTime::now(),
+            payload,
+        }
+    }
+
+    /// Gets the payload if it is a message of the type.
+    pub fn payload<M: 'static>(&self) -> Option<&M> {
+        self.payload.as_ref()?.downcast_ref()
+    }
+
+    /// Gets the event that was sent directly or as an `Event` message.
+    pub fn event<E: 'static>(&self) -> Option<&E> {
+        self.payload::<E>()
+            .or_else(|| self.payload::<Event<E>>().map(|event| &event.event))
+    }
+}
+
+/// Sends the letter to the global dead-letter recipient.
+pub(crate) fn post<M: 'static>(letter: impl FnOnce() -> DeadLetter) {
+    // Prevents loops if the dead-letter agent is gone itself
+    if TypeId::of::<M>() == TypeId::of::<Event<DeadLetter>>() {
+        return;
+    }
+    if let Some(recipient) = CRB.dead_letters() {
+        recipient.send(letter()).ok();
+    }
+}
+
+/// Sends a message that was left in a mailbox to the global dead-letter recipient.
+pub(crate) fn post_envelope<A: Agent>(envelope: Envelope<A>, reason: DeadReason) {
+    let message = envelope.message_name();
+    let payload = match envelope.as_any().downcast::<WithHeaders<A>>() {
+        Ok(with_headers) => with_headers.envelope.as_any(),
+        Err(payload) => payload,
+    };
+    // Prevents loops if the dead-letter agent is gone itself
+    if payload.is::<Event<DeadLetter>>() {
+        return;
+    }
+    if let Some(recipient) = CRB.dead_letters() {
+        let letter = DeadLetter {
+            target: type_name::<A>(),
+            message,
+            reason,
+            timestamp: SystemTime::now(),
+            payload: Some(payload),
+        };
+        recipient.send(letter).ok();
+    }
+}
+
+/// A route of dead letters.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct DeadRoute {
+    pub target: &'static str,
+    pub message: &'static str,
+    pub reason: DeadReason,
+}
+
+/// Counters of dead letters that can be read outside of the agent.
+#[derive(Debug, Clone, Default)]
+pub struct DeadLetterStats {
+    counters: Arc<Mutex<HashMap<DeadRoute, usize>>>,
+}
+
+impl DeadLetterStats {
+    pub fn total(&self) -> usize {
+        self.lock().values().sum()
+    }
+
+    pub fn count(&self, reason: DeadReason) -> usize {
+        self.lock()
+            .iter()
+            .filter(|(route, _)| route.reason == reason)
+            .map(|(_, count)| count)
+            .sum()
+    }
+
+    pub fn routes(&self) -> Vec<(DeadRoute, usize)> {
+        let mut routes: Vec<_> = self
+            .lock()
+            .iter()
+            .map(|(route, count)| (route.clone(), *count))
+            .collect();
+        routes.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+        routes
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<DeadRoute, usize>> {
+        self.counters.lock().unwrap_or_else(|err| err.into_inner())
+    }
+}
+
+/// An agent that logs and counts dead letters.
+///
+/// Register its recipient with `CRB.set_dead_letters` to collect
+/// letters of all agents.
+#[derive(Default)]
+pub struct DeadLetters {
+    stats: DeadLetterStats,
+}
+
+impl DeadLetters {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn stats(&self) -> DeadLetterStats {
+        self.stats.clone()
+    }
+}
+
+impl Standalone for DeadLetters {}
+
+impl Agent for DeadLetters {
+    type Context = AgentSession<Self>;
+}
+
+#[async_trait]
+impl OnEvent<DeadLetter> for DeadLetters {
+    async fn handle(&mut self, letter: DeadLetter, _ctx: &mut Context<Self>) -> Result<()> {
+        log::warn!(
+            "Dead letter {} for {}: {:?}",
+            letter.message,
+            letter.target,
+            letter.reason
+        );
+        let route = DeadRoute {
+            target: letter.target,
+            message: letter.message,
+            reason: letter.reason,
+        };
+        *self.stats.lock().entry(route).or_default() += 1;
+        Ok(())
+    }
+}
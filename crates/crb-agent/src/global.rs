@@ -1,15 +1,20 @@
+use crate::dead_letter::DeadLetter;
 use core::sync::atomic::{AtomicUsize, Ordering};
+use crb_send::Recipient;
+use std::sync::RwLock;
 
 pub static CRB: Global = Global::new();
 
 pub struct Global {
     long_threshold: AtomicUsize,
+    dead_letters: RwLock<Option<Recipient<DeadLetter>>>,
 }
 
 impl Global {
     const fn new() -> Self {
         Self {
             long_threshold: AtomicUsize::new(usize::MAX),
+            dead_letters: RwLock::new(None),
         }
     }
 
@@ -20,4 +25,19 @@ impl Global {
     pub fn get_long_threshold(&self) -> usize {
         self.long_threshold.load(Ordering::Relaxed)
     }
+
+    /// Sets the recipient of messages that can't be delivered or handled.
+    pub fn set_dead_letters(&self, recipient: Option<Recipient<DeadLetter>>) {
+        *self
+            .dead_letters
+            .write()
+            .unwrap_or_else(|err| err.into_inner()) = recipient;
+    }
+
+    pub fn dead_letters(&self) -> Option<Recipient<DeadLetter>> {
+        self.dead_letters
+            .read()
+            .unwrap_or_else(|err| err.into_inner())
+            .clone()
+    }
 }
@@ -2,21 +2,29 @@ pub mod address;
 pub mod address_ext;
 pub mod agent;
 pub mod context;
+pub mod dead_letter;
 pub mod extension;
 pub mod global;
 pub mod message;
 pub mod performers;
+pub mod repair;
 pub mod runtime;
+pub mod stash;
 
-pub use address::{Address, Envelope, MessageFor};
+pub use address::{Address, AsAny, Envelope, MessageFor};
 pub use address_ext::{Equip, StopAddress, StopRecipient, ToAddress, ToRecipient, UniAddress};
 pub use agent::{Agent, Runnable, Standalone};
 pub use context::{AgentContext, AgentSession, Context};
+pub use dead_letter::{DeadLetter, DeadLetterStats, DeadLetters, DeadReason, DeadRoute};
 pub use global::{Global, CRB};
 pub use message::event::{Event, EventExt, OnEvent, TheEvent};
 pub use performers::async_performer::DoAsync;
+pub use performers::hybrid_performer::MailboxPolicy;
+pub use performers::nested_performer::{NestedState, NestedStates, StateTransition, TopState};
 pub use performers::Next;
+pub use repair::{Backoff, CircuitBreaker, CircuitChanged, CircuitState, Resilient};
 pub use runtime::RunAgent;
+pub use stash::{Stash, StashOverflow};
 
 #[cfg(feature = "sync")]
 pub use performers::sync_performer::DoSync;
@@ -1,10 +1,13 @@
 use crate::address::{Address, Envelope, MessageFor};
 use crate::agent::Agent;
 use crate::context::Context;
+use crate::dead_letter::{self, DeadLetter, DeadReason};
+use crate::message::receive::take_event;
 use anyhow::{anyhow, Error, Result};
 use async_trait::async_trait;
 use crb_core::Tag;
-use crb_send::Recipient;
+use crb_send::{Headers, Recipient};
+use std::any::Any;
 
 pub trait EventExt<E: TheEvent> {
     fn event(&self, event: E) -> Result<()>;
@@ -52,6 +55,14 @@ impl<A: Agent> Address<A> {
         self.send(Event::new_tagged(event, tag))
     }
 
+    pub fn event_with<E>(&self, event: E, headers: Headers) -> Result<()>
+    where
+        A: OnEvent<E>,
+        E: TheEvent,
+    {
+        self.send_with(Event::new(event), headers)
+    }
+
     pub fn recipient<E>(&self) -> Recipient<E>
     where
         A: OnEvent<E>,
@@ -88,6 +99,14 @@ impl<A: Agent> Context<A> {
         self.address().event_tagged(event, tag)
     }
 
+    pub fn event_with<E>(&self, event: E, headers: Headers) -> Result<()>
+    where
+        A: OnEvent<E>,
+        E: TheEvent,
+    {
+        self.address().event_with(event, headers)
+    }
+
     pub fn recipient<E>(&self) -> Recipient<E>
     where
         A: OnEvent<E>,
@@ -116,7 +135,11 @@ pub trait OnEvent<E: TheEvent, T: Tag = ()>: Agent {
         self.handle(event, ctx).await
     }
 
-    async fn handle(&mut self, _event: E, _ctx: &mut Context<Self>) -> Result<()> {
+    async fn handle(&mut self, event: E, _ctx: &mut Context<Self>) -> Result<()> {
+        dead_letter::post::<E>(|| {
+            let payload: Box<dyn Any + Send> = Box::new(event);
+            DeadLetter::new::<Self, E>(DeadReason::Unhandled, Some(payload))
+        });
         Err(anyhow!("The handle method in not implemented."))
     }
 
@@ -126,7 +149,7 @@ pub trait OnEvent<E: TheEvent, T: Tag = ()>: Agent {
 }
 
 pub struct Event<E, T = ()> {
-    event: E,
+    pub(crate) event: E,
     tag: T,
 }
 
@@ -142,6 +165,17 @@ impl<E> Event<E> {
     {
         Self::new(event).into_envelope::<A>()
     }
+
+    /// Takes the untagged event out of the envelope or returns the envelope back.
+    ///
+    /// Headers of the message are dropped.
+    pub fn take<A>(envelope: Envelope<A>) -> Result<E, Envelope<A>>
+    where
+        A: Agent,
+        E: TheEvent,
+    {
+        take_event(envelope, &|_: &E| true)
+    }
 }
 
 impl<E, T> Event<E, T> {
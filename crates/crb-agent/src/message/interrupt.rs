@@ -21,6 +21,10 @@ impl<A: Agent> MessageFor<A> for Interrupt {
         agent.interrupt(ctx);
         Ok(())
     }
+
+    fn is_control(&self) -> bool {
+        true
+    }
 }
 
 impl<A: Agent> Interruptor for Address<A> {
@@ -0,0 +1,99 @@
+use crate::address::{Envelope, WithHeaders};
+use crate::agent::Agent;
+use crate::context::{AgentContext, Context};
+use crate::message::event::{Event, OnEvent, TheEvent};
+
+impl<A: Agent> Context<A> {
+    /// Waits for the next event of the type.
+    ///
+    /// Only untagged events sent as `Event<E>` are matched,
+    /// events sent with `event_tagged` stay in the mailbox.
+    /// Other messages are kept in the mailbox in their order.
+    /// Returns `None` if the mailbox is closed or the agent has been interrupted.
+    pub async fn receive<E>(&mut self) -> Option<E>
+    where
+        A: OnEvent<E>,
+        E: TheEvent,
+    {
+        self.receive_if(|_: &E| true).await
+    }
+
+    /// Waits for the next event of the type that matches the predicate.
+    ///
+    /// Cancel-safe: if the future is dropped, skipped messages
+    /// are handled on the next step of the agent.
+    pub async fn receive_if<E, F>(&mut self, predicate: F) -> Option<E>
+    where
+        A: OnEvent<E>,
+        E: TheEvent,
+        F: Fn(&E) -> bool + Send,
+    {
+        // Messages skipped by a canceled call are checked again
+        self.session().stash.release_held();
+        let received = loop {
+            let Some(envelope) = self.next_envelope().await else {
+                break None;
+            };
+            if envelope.is_control() {
+                self.session().stash.hold(envelope);
+                break None;
+            }
+            let Some(envelope) = check_deadline(envelope, self) else {
+                continue;
+            };
+            match take_event(envelope, &predicate) {
+                Ok(event) => break Some(event),
+                Err(envelope) => self.session().stash.hold(envelope),
+            }
+        };
+        self.session().stash.release_held();
+        received
+    }
+}
+
+/// Drops the expired message the same way as the handling does.
+fn check_deadline<A: Agent>(envelope: Envelope<A>, ctx: &mut Context<A>) -> Option<Envelope<A>> {
+    if !(*envelope).as_any_ref().is::<WithHeaders<A>>() {
+        return Some(envelope);
+    }
+    let with_headers = envelope
+        .as_any()
+        .downcast::<WithHeaders<A>>()
+        .expect("The type of the envelope has been checked");
+    with_headers
+        .check_deadline(ctx)
+        .map(|with_headers| with_headers as Envelope<A>)
+}
+
+pub(crate) fn take_event<A, E, F>(envelope: Envelope<A>, predicate: &F) -> Result<E, Envelope<A>>
+where
+    A: Agent,
+    E: TheEvent,
+    F: Fn(&E) -> bool,
+{
+    if (*envelope).as_any_ref().is::<WithHeaders<A>>() {
+        let mut with_headers = envelope
+            .as_any()
+            .downcast::<WithHeaders<A>>()
+            .expect("The type of the envelope has been checked");
+        return match take_event(with_headers.envelope, predicate) {
+            Ok(event) => Ok(event),
+            Err(envelope) => {
+                with_headers.envelope = envelope;
+                Err(with_headers)
+            }
+        };
+    }
+    let matched = (*envelope)
+        .as_any_ref()
+        .downcast_ref::<Event<E>>()
+        .is_some_and(|event| predicate(&event.event));
+    if !matched {
+        return Err(envelope);
+    }
+    let event = envelope
+        .as_any()
+        .downcast::<Event<E>>()
+        .expect("The type of the event has been checked");
+    Ok(event.event)
+}
@@ -5,6 +5,7 @@ use crate::performers::{AgentState, Next, StatePerformer, Transition, Transition
 use anyhow::{Error, Result};
 use async_trait::async_trait;
 use crb_core::time::Instant;
+use crb_runtime::Stopper;
 use std::marker::PhantomData;
 
 impl<T> Next<T>
@@ -26,32 +27,9 @@ where
 
 #[async_trait]
 pub trait DoAsync<S: Send + 'static = ()>: Agent {
-    async fn handle(&mut self, mut state: S, ctx: &mut Context<Self>) -> Result<Next<Self>> {
+    async fn handle(&mut self, state: S, ctx: &mut Context<Self>) -> Result<Next<Self>> {
         let stopper = ctx.session().controller.stopper.clone();
-        while stopper.is_active() {
-            let iteration = Instant::now();
-
-            let result = self.repeat(&mut state).await;
-            match result {
-                Ok(Some(state)) => {
-                    return Ok(state);
-                }
-                Ok(None) => {}
-                Err(err) => {
-                    self.repair(err).await?;
-                }
-            }
-
-            if iteration.elapsed().as_millis() as usize >= CRB.get_long_threshold() {
-                use std::any::type_name;
-                log::warn!(
-                    "DoAsync<{}> for {} is too long!",
-                    type_name::<S>(),
-                    type_name::<Self>()
-                );
-            }
-        }
-        Ok(Next::interrupt())
+        repeat_while_active(self, state, &stopper).await
     }
 
     async fn repeat(&mut self, state: &mut S) -> Result<Option<Next<Self>>> {
@@ -75,6 +53,47 @@ pub trait DoAsync<S: Send + 'static = ()>: Agent {
     }
 }
 
+/// The default loop of `handle` that calls `repeat` until the agent is stopped.
+pub(crate) async fn repeat_while_active<T, S>(
+    agent: &mut T,
+    mut state: S,
+    stopper: &Stopper,
+) -> Result<Next<T>>
+where
+    T: DoAsync<S>,
+    S: Send + 'static,
+{
+    while stopper.is_active() {
+        let iteration = Instant::now();
+
+        let result = agent.repeat(&mut state).await;
+        match result {
+            Ok(Some(state)) => {
+                return Ok(state);
+            }
+            Ok(None) => {}
+            Err(err) => {
+                agent.repair(err).await?;
+            }
+        }
+
+        warn_if_long::<T, S>(iteration);
+    }
+    Ok(Next::interrupt())
+}
+
+/// Warns if an iteration of the state started at `iteration` is too long.
+pub(crate) fn warn_if_long<T, S>(iteration: Instant) {
+    if iteration.elapsed().as_millis() as usize >= CRB.get_long_threshold() {
+        use std::any::type_name;
+        log::warn!(
+            "DoAsync<{}> for {} is too long!",
+            type_name::<S>(),
+            type_name::<T>()
+        );
+    }
+}
+
 struct AsyncPerformer<T, S> {
     _task: PhantomData<T>,
     state: Option<S>,
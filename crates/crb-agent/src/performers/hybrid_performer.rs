@@ -0,0 +1,152 @@
+use crate::address::Envelope;
+use crate::agent::Agent;
+use crate::context::{AgentContext, Context};
+use crate::dead_letter::{self, DeadReason};
+use crate::performers::async_performer::DoAsync;
+use crate::performers::{AgentState, Next, StatePerformer, Transition, TransitionCommand};
+use anyhow::Result;
+use async_trait::async_trait;
+use futures::future::{select, Either};
+
+/// What to do with messages that arrive while a hybrid state is active.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MailboxPolicy {
+    /// Handle messages as they arrive, the pending iteration is canceled.
+    #[default]
+    Handle,
+    /// Keep messages and handle them when the state is finished.
+    Defer,
+    /// Send messages to dead letters. Requests are canceled for their senders.
+    Reject,
+}
+
+impl<T> Next<T>
+where
+    T: Agent,
+{
+    /// Performs the `DoAsync` state and handles incoming messages
+    /// while `repeat` is pending.
+    ///
+    /// The state is driven by `repeat`, `repair` and `fallback_with_context`,
+    /// an overridden `handle` of the state is never called.
+    ///
+    /// The handler needs the agent, so a message cancels the pending iteration
+    /// and `repeat` is called again after the message is handled.
+    /// `repeat` must be cancel-safe: keep the progress in the agent or in the state.
+    pub fn do_hybrid<S>(state: S) -> Self
+    where
+        T: DoAsync<S>,
+        S: AgentState,
+    {
+        Self::do_hybrid_with(state, MailboxPolicy::Handle)
+    }
+
+    pub fn do_hybrid_with<S>(state: S, policy: MailboxPolicy) -> Self
+    where
+        T: DoAsync<S>,
+        S: AgentState,
+    {
+        let performer = HybridPerformer {
+            state: Some(state),
+            policy,
+        };
+        Self::new(performer)
+    }
+}
+
+struct HybridPerformer<S> {
+    state: Option<S>,
+    policy: MailboxPolicy,
+}
+
+enum Step<T: Agent> {
+    Done(Result<Option<Next<T>>>),
+    /// The iteration has been canceled to handle the message.
+    Message(Envelope<T>),
+}
+
+/// Runs an iteration of the state and takes incoming messages while it's pending.
+async fn iterate<T, S>(
+    policy: MailboxPolicy,
+    agent: &mut T,
+    state: &mut S,
+    ctx: &mut Context<T>,
+    deferred: &mut Vec<Envelope<T>>,
+) -> Step<T>
+where
+    T: DoAsync<S>,
+    S: AgentState,
+{
+    let mut iteration = agent.repeat(state);
+    let mut mailbox_open = true;
+    loop {
+        if !mailbox_open {
+            return Step::Done(iteration.await);
+        }
+        match select(&mut iteration, ctx.next_envelope()).await {
+            Either::Left((result, _)) => return Step::Done(result),
+            Either::Right((None, _)) => mailbox_open = false,
+            Either::Right((Some(envelope), _)) => match policy {
+                _ if envelope.is_control() => return Step::Message(envelope),
+                MailboxPolicy::Handle => return Step::Message(envelope),
+                MailboxPolicy::Defer => deferred.push(envelope),
+                MailboxPolicy::Reject => {
+                    log::trace!("The message has been rejected by a hybrid state");
+                    dead_letter::post_envelope(envelope, DeadReason::Rejected);
+                }
+            },
+        }
+    }
+}
+
+async fn handle<T: Agent>(envelope: Envelope<T>, agent: &mut T, ctx: &mut Context<T>) {
+    if let Err(err) = envelope.handle(agent, ctx).await {
+        agent.failed(err, ctx);
+    }
+}
+
+#[async_trait]
+impl<T, S> StatePerformer<T> for HybridPerformer<S>
+where
+    T: DoAsync<S>,
+    S: AgentState,
+{
+    async fn perform(&mut self, mut agent: T, ctx: &mut Context<T>) -> Transition<T> {
+        let mut state = self.state.take().unwrap();
+        let stopper = ctx.session().controller.stopper.clone();
+        let mut deferred = Vec::new();
+        let result: Result<Next<T>> = loop {
+            if !stopper.is_active() {
+                break Ok(Next::interrupt());
+            }
+            let step = iterate(self.policy, &mut agent, &mut state, ctx, &mut deferred).await;
+            match step {
+                Step::Done(Ok(Some(next_state))) => break Ok(next_state),
+                Step::Done(Ok(None)) => {}
+                Step::Done(Err(err)) => {
+                    if let Err(err) = agent.repair(err).await {
+                        break Err(err);
+                    }
+                }
+                Step::Message(envelope) => {
+                    handle(envelope, &mut agent, ctx).await;
+                    if let Some(next_state) = ctx.session().next_state.take() {
+                        break Ok(next_state);
+                    }
+                }
+            }
+        };
+        let mut next_state = match result {
+            Ok(next) => next,
+            Err(err) => agent.fallback_with_context(err, ctx).await,
+        };
+        for envelope in deferred {
+            handle(envelope, &mut agent, ctx).await;
+            if let Some(requested) = ctx.session().next_state.take() {
+                next_state = requested;
+            }
+        }
+        let command = TransitionCommand::Next(next_state);
+        Transition::Continue { agent, command }
+    }
+}
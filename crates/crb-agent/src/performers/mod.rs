@@ -1,7 +1,9 @@
 pub mod async_performer;
 pub mod consume_performer;
 pub mod events_performer;
+pub mod hybrid_performer;
 pub mod interrupt_performer;
+pub mod nested_performer;
 
 #[cfg(feature = "sync")]
 pub mod sync_performer;
@@ -19,12 +21,15 @@ impl<T> AgentState for T where T: Send + 'static {}
 
 pub struct Next<T: ?Sized> {
     pub(crate) transition: Box<dyn StatePerformer<T>>,
+    /// Other states leave active nested states before they are performed.
+    pub(crate) nested: bool,
 }
 
 impl<T: Agent> Next<T> {
     pub fn new(performer: impl StatePerformer<T>) -> Self {
         Self {
             transition: Box::new(performer),
+            nested: false,
         }
     }
 }
@@ -0,0 +1,224 @@
+use crate::agent::Agent;
+use crate::context::Context;
+use crate::performers::async_performer::DoAsync;
+use crate::performers::{AgentState, Next, StatePerformer, Transition, TransitionCommand};
+use anyhow::{Error, Result};
+use async_trait::async_trait;
+use std::any::{type_name, TypeId};
+use std::collections::VecDeque;
+
+/// The number of transitions kept in the history.
+const HISTORY_LIMIT: usize = 64;
+
+impl<T> Next<T>
+where
+    T: Agent,
+{
+    /// Enters the nested state.
+    ///
+    /// Leaves active states that are not parents of the new one
+    /// and enters the new state and its parents that are not active yet.
+    pub fn enter<S>(state: S) -> Self
+    where
+        T: DoAsync<S>,
+        S: NestedState<T>,
+    {
+        let performer = NestedPerformer { state: Some(state) };
+        let mut next = Self::new(performer);
+        next.nested = true;
+        next
+    }
+}
+
+/// A state that can be a substate of a parent state.
+///
+/// Hooks of parents are called for substates as well: entering a substate
+/// enters all its parents first, and errors of a substate that are not handled
+/// by `on_error` are passed to its parent. Only the `DoAsync` implementation
+/// of the substate itself is performed, parents share nothing but the hooks.
+///
+/// Any state that is not entered with `Next::enter` leaves all active nested states,
+/// including `Next::events()`, so messages are handled by the handlers of the agent
+/// and never by nested states.
+pub trait NestedState<A: Agent>: AgentState {
+    type Parent: NestedState<A>;
+
+    fn on_enter(_agent: &mut A, _ctx: &mut Context<A>) {}
+
+    fn on_exit(_agent: &mut A, _ctx: &mut Context<A>) {}
+
+    fn on_error(_agent: &mut A, err: Error, _ctx: &mut Context<A>) -> Result<Next<A>> {
+        Err(err)
+    }
+}
+
+/// The topmost parent of all nested states.
+pub struct TopState;
+
+impl<A: Agent> NestedState<A> for TopState {
+    type Parent = TopState;
+}
+
+struct Layer<A: Agent> {
+    id: TypeId,
+    name: &'static str,
+    on_enter: fn(&mut A, &mut Context<A>),
+    on_exit: fn(&mut A, &mut Context<A>),
+    on_error: fn(&mut A, Error, &mut Context<A>) -> Result<Next<A>>,
+}
+
+impl<A: Agent> Layer<A> {
+    fn path<S: NestedState<A>>(path: &mut Vec<Layer<A>>, visited: &mut Vec<TypeId>) {
+        let id = TypeId::of::<S>();
+        if id == TypeId::of::<TopState>() {
+            return;
+        }
+        if visited.contains(&id) {
+            // A cycle of parents is cut to avoid the endless recursion
+            log::error!("Nested state {} is a parent of itself", type_name::<S>());
+            return;
+        }
+        visited.push(id);
+        Self::path::<S::Parent>(path, visited);
+        path.push(Layer {
+            id,
+            name: type_name::<S>(),
+            on_enter: S::on_enter,
+            on_exit: S::on_exit,
+            on_error: S::on_error,
+        });
+    }
+}
+
+/// A record about a transition between nested states.
+///
+/// `None` is a state that is not nested: `from` is `None` if no nested state
+/// was active and `to` is `None` if all nested states have been left.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StateTransition {
+    pub from: Option<&'static str>,
+    pub to: Option<&'static str>,
+}
+
+/// Active nested states of an agent and the history of transitions.
+pub struct NestedStates<A: Agent> {
+    active: Vec<Layer<A>>,
+    history: VecDeque<StateTransition>,
+}
+
+impl<A: Agent> Default for NestedStates<A> {
+    fn default() -> Self {
+        Self {
+            active: Vec::new(),
+            history: VecDeque::new(),
+        }
+    }
+}
+
+impl<A: Agent> NestedStates<A> {
+    /// Checks if the state or one of its substates is active.
+    pub fn is_in<S: AgentState>(&self) -> bool {
+        let id = TypeId::of::<S>();
+        self.active.iter().any(|layer| layer.id == id)
+    }
+
+    /// Names of active states starting from the topmost one.
+    pub fn active(&self) -> Vec<&'static str> {
+        self.active.iter().map(|layer| layer.name).collect()
+    }
+
+    /// Recent transitions starting from the oldest one.
+    pub fn history(&self) -> impl Iterator<Item = &StateTransition> {
+        self.history.iter()
+    }
+
+    fn record(&mut self, transition: StateTransition) {
+        if self.history.len() >= HISTORY_LIMIT {
+            self.history.pop_front();
+        }
+        self.history.push_back(transition);
+    }
+}
+
+/// Leaves all active nested states.
+pub(crate) fn leave_all<A: Agent>(agent: &mut A, ctx: &mut Context<A>) {
+    if let Some(layer) = ctx.states.active.last() {
+        let from = Some(layer.name);
+        ctx.states.record(StateTransition { from, to: None });
+    }
+    while let Some(layer) = ctx.states.active.pop() {
+        (layer.on_exit)(agent, ctx);
+    }
+}
+
+struct NestedPerformer<S> {
+    state: Option<S>,
+}
+
+#[async_trait]
+impl<T, S> StatePerformer<T> for NestedPerformer<S>
+where
+    T: DoAsync<S>,
+    S: NestedState<T>,
+{
+    async fn perform(&mut self, mut agent: T, ctx: &mut Context<T>) -> Transition<T> {
+        let state = self.state.take().unwrap();
+
+        let mut target = Vec::new();
+        Layer::path::<S>(&mut target, &mut Vec::new());
+        let from = ctx.states.active.last().map(|layer| layer.name);
+        let common = ctx
+            .states
+            .active
+            .iter()
+            .zip(&target)
+            .take_while(|(active, target)| active.id == target.id)
+            .count()
+            // The state is re-entered if the transition targets itself
+            .min(target.len().saturating_sub(1));
+        while ctx.states.active.len() > common {
+            if let Some(layer) = ctx.states.active.pop() {
+                (layer.on_exit)(&mut agent, ctx);
+            }
+        }
+        for layer in target.drain(common..) {
+            let on_enter = layer.on_enter;
+            ctx.states.active.push(layer);
+            on_enter(&mut agent, ctx);
+        }
+        let to = Some(type_name::<S>());
+        ctx.states.record(StateTransition { from, to });
+
+        let next_state = match agent.handle(state, ctx).await {
+            Ok(next) => next,
+            Err(err) => bubble::<T, S>(&mut agent, err, ctx).await,
+        };
+        let command = TransitionCommand::Next(next_state);
+        Transition::Continue { agent, command }
+    }
+}
+
+/// Passes an error from the innermost state to the topmost one
+/// and falls back if no state has handled it.
+async fn bubble<T, S>(agent: &mut T, mut err: Error, ctx: &mut Context<T>) -> Next<T>
+where
+    T: DoAsync<S>,
+    S: NestedState<T>,
+{
+    let handlers: Vec<_> = ctx
+        .states
+        .active
+        .iter()
+        .rev()
+        .map(|layer| layer.on_error)
+        .collect();
+    for on_error in handlers {
+        match on_error(agent, err, ctx) {
+            Ok(next) => return next,
+            Err(unhandled) => {
+                err = unhandled;
+            }
+        }
+    }
+    DoAsync::<S>::fallback_with_context(agent, err, ctx).await
+}
@@ -29,31 +29,8 @@ where
 }
 
 pub trait DoSync<S = ()>: Agent {
-    fn perform(&mut self, mut state: S, stopper: Stopper) -> Result<Next<Self>> {
-        while stopper.is_active() {
-            let iteration = Instant::now();
-
-            let result = self.repeat(&mut state);
-            match result {
-                Ok(Some(state)) => {
-                    return Ok(state);
-                }
-                Ok(None) => {}
-                Err(err) => {
-                    self.repair(err)?;
-                }
-            }
-
-            if iteration.elapsed().as_millis() as usize >= CRB.get_long_threshold() {
-                use std::any::type_name;
-                log::warn!(
-                    "DoAsync<{}> for {} is too long!",
-                    type_name::<S>(),
-                    type_name::<Self>()
-                );
-            }
-        }
-        Ok(Next::interrupt())
+    fn perform(&mut self, state: S, stopper: Stopper) -> Result<Next<Self>> {
+        repeat_while_active(self, state, &stopper)
     }
 
     fn repeat(&mut self, state: &mut S) -> Result<Option<Next<Self>>> {
@@ -73,6 +50,41 @@ pub trait DoSync<S = ()>: Agent {
     }
 }
 
+/// The default loop of `perform` that calls `repeat` until the agent is stopped.
+pub(crate) fn repeat_while_active<T, S>(
+    agent: &mut T,
+    mut state: S,
+    stopper: &Stopper,
+) -> Result<Next<T>>
+where
+    T: DoSync<S>,
+{
+    while stopper.is_active() {
+        let iteration = Instant::now();
+
+        let result = agent.repeat(&mut state);
+        match result {
+            Ok(Some(state)) => {
+                return Ok(state);
+            }
+            Ok(None) => {}
+            Err(err) => {
+                agent.repair(err)?;
+            }
+        }
+
+        if iteration.elapsed().as_millis() as usize >= CRB.get_long_threshold() {
+            use std::any::type_name;
+            log::warn!(
+                "DoAsync<{}> for {} is too long!",
+                type_name::<S>(),
+                type_name::<T>()
+            );
+        }
+    }
+    Ok(Next::interrupt())
+}
+
 struct SyncPerformer<T, S> {
     _task: PhantomData<T>,
     state: Option<S>,
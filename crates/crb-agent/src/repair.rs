@@ -0,0 +1,435 @@
+//! Reusable policies for the `repair` method of `DoAsync` and `DoSync` states.
+
+use crate::address_ext::ToRecipient;
+use crate::context::{AgentContext, Context};
+use crate::performers::async_performer::{self, DoAsync};
+#[cfg(feature = "sync")]
+use crate::performers::sync_performer::{self, DoSync};
+use crate::performers::{AgentState, Next};
+use anyhow::{Error, Result};
+use async_trait::async_trait;
+use crb_core::time::{sleep, Duration, Instant};
+use crb_runtime::Stopper;
+use crb_send::{Recipient, Sender};
+
+type Predicate = Box<dyn Fn(&Error) -> bool + Send + Sync>;
+
+/// How often a delay checks that the agent is not stopped.
+const STOPPER_CHECK: Duration = Duration::from_millis(10);
+
+/// Returns the remaining part of the delay that fits before the next check of the stopper.
+fn next_slice(deadline: Instant, stopper: Option<&Stopper>) -> Option<Duration> {
+    let remained = deadline.saturating_duration_since(Instant::now());
+    match stopper {
+        _ if remained.is_zero() => None,
+        Some(stopper) if !stopper.is_active() => None,
+        Some(_) => Some(remained.min(STOPPER_CHECK)),
+        None => Some(remained),
+    }
+}
+
+/// Sleeps for the delay or until the stopper fires.
+async fn sleep_or_stop(delay: Duration, stopper: Option<&Stopper>) {
+    let deadline = Instant::now() + delay;
+    while let Some(slice) = next_slice(deadline, stopper) {
+        sleep(slice).await;
+    }
+}
+
+fn sleep_or_stop_sync(delay: Duration, stopper: Option<&Stopper>) {
+    let deadline = Instant::now() + delay;
+    while let Some(slice) = next_slice(deadline, stopper) {
+        std::thread::sleep(slice);
+    }
+}
+
+/// Delays the next attempt after a failure.
+///
+/// Attach it to a state with `Resilient` or call `reset` after a successful
+/// iteration to start counting attempts from scratch.
+/// A delay ends early when the stopper set by `with_stopper` fires,
+/// `Resilient` sets the stopper of the agent.
+pub struct Backoff {
+    initial: Duration,
+    max_delay: Duration,
+    factor: f64,
+    jitter: f64,
+    max_attempts: Option<usize>,
+    retry_if: Option<Predicate>,
+    attempts: usize,
+    /// Stops growing when the delay reaches the maximum.
+    exponent: i32,
+    stopper: Option<Stopper>,
+}
+
+impl Backoff {
+    /// Doubles the delay after every failed attempt.
+    pub fn exponential(initial: Duration) -> Self {
+        Self {
+            initial,
+            max_delay: Duration::from_secs(30),
+            factor: 2.0,
+            jitter: 0.0,
+            max_attempts: None,
+            retry_if: None,
+            attempts: 0,
+            exponent: 0,
+            stopper: None,
+        }
+    }
+
+    pub fn constant(delay: Duration) -> Self {
+        Self::exponential(delay).with_factor(1.0)
+    }
+
+    pub fn with_factor(mut self, factor: f64) -> Self {
+        self.factor = factor.max(1.0);
+        self
+    }
+
+    pub fn with_max_delay(mut self, max_delay: Duration) -> Self {
+        self.max_delay = max_delay;
+        self
+    }
+
+    /// Randomly shortens a delay up to the fraction of it.
+    pub fn with_jitter(mut self, jitter: f64) -> Self {
+        self.jitter = jitter.clamp(0.0, 1.0);
+        self
+    }
+
+    pub fn max_attempts(mut self, max_attempts: usize) -> Self {
+        self.max_attempts = Some(max_attempts);
+        self
+    }
+
+    /// Retries only errors that match the predicate.
+    pub fn retry_if<F>(mut self, predicate: F) -> Self
+    where
+        F: Fn(&Error) -> bool + Send + Sync + 'static,
+    {
+        self.retry_if = Some(Box::new(predicate));
+        self
+    }
+
+    pub fn with_stopper(mut self, stopper: Stopper) -> Self {
+        self.stopper = Some(stopper);
+        self
+    }
+
+    pub fn attempts(&self) -> usize {
+        self.attempts
+    }
+
+    pub fn reset(&mut self) {
+        self.attempts = 0;
+        self.exponent = 0;
+    }
+
+    /// Returns the delay before the next attempt or the error
+    /// if it can't be retried.
+    pub fn next_delay(&mut self, err: Error) -> Result<Duration> {
+        if let Some(retry_if) = self.retry_if.as_ref() {
+            if !retry_if(&err) {
+                return Err(err);
+            }
+        }
+        if self.max_attempts.is_some_and(|max| self.attempts >= max) {
+            return Err(err);
+        }
+        self.attempts = self.attempts.saturating_add(1);
+        // The delay is capped before the `Duration` is built to never overflow it
+        let max_delay = self.max_delay.as_secs_f64();
+        let secs = self.initial.as_secs_f64() * self.factor.powi(self.exponent);
+        let secs = secs.min(max_delay);
+        if secs > 0.0 && secs < max_delay {
+            self.exponent = self.exponent.saturating_add(1);
+        }
+        let delay = Duration::try_from_secs_f64(secs).unwrap_or(self.max_delay);
+        let jitter = self.jitter * fastrand::f64();
+        Ok(delay.mul_f64(1.0 - jitter))
+    }
+
+    pub async fn repair(&mut self, err: Error) -> Result<()> {
+        let delay = self.next_delay(err)?;
+        sleep_or_stop(delay, self.stopper.as_ref()).await;
+        Ok(())
+    }
+
+    pub fn repair_sync(&mut self, err: Error) -> Result<()> {
+        let delay = self.next_delay(err)?;
+        sleep_or_stop_sync(delay, self.stopper.as_ref());
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CircuitState {
+    /// Attempts are allowed.
+    Closed,
+    /// Attempts are paused until the cooldown ends.
+    Open,
+    /// A single attempt is allowed to check the recovery.
+    HalfOpen,
+}
+
+/// An event about a state change of a `CircuitBreaker`.
+#[derive(Debug, Clone)]
+pub struct CircuitChanged {
+    pub from: CircuitState,
+    pub to: CircuitState,
+}
+
+/// Pauses attempts for a cooldown after repeated failures.
+///
+/// Attach it to a state with `Resilient` or await `ready` at the beginning
+/// of every iteration and report results with `success` and `failure`.
+/// The circuit stays open if the stopper set by `with_stopper` fires during the cooldown.
+pub struct CircuitBreaker {
+    threshold: usize,
+    cooldown: Duration,
+    failures: usize,
+    state: CircuitState,
+    opened_at: Instant,
+    observers: Vec<Recipient<CircuitChanged>>,
+    stopper: Option<Stopper>,
+}
+
+impl CircuitBreaker {
+    pub fn new(threshold: usize, cooldown: Duration) -> Self {
+        Self {
+            threshold: threshold.max(1),
+            cooldown,
+            failures: 0,
+            state: CircuitState::Closed,
+            opened_at: Instant::now(),
+            observers: Vec::new(),
+            stopper: None,
+        }
+    }
+
+    pub fn with_stopper(mut self, stopper: Stopper) -> Self {
+        self.stopper = Some(stopper);
+        self
+    }
+
+    pub fn report_to(&mut self, recipient: impl ToRecipient<CircuitChanged>) {
+        self.observers.push(recipient.to_recipient());
+    }
+
+    pub fn state(&self) -> CircuitState {
+        self.state
+    }
+
+    /// Waits for the end of the cooldown if the circuit is open.
+    pub async fn ready(&mut self) {
+        if let Some(remained) = self.remained() {
+            sleep_or_stop(remained, self.stopper.as_ref()).await;
+            self.half_open();
+        }
+    }
+
+    pub fn ready_sync(&mut self) {
+        if let Some(remained) = self.remained() {
+            sleep_or_stop_sync(remained, self.stopper.as_ref());
+            self.half_open();
+        }
+    }
+
+    fn half_open(&mut self) {
+        let stopped = self
+            .stopper
+            .as_ref()
+            .is_some_and(|stopper| !stopper.is_active());
+        if !stopped {
+            self.switch(CircuitState::HalfOpen);
+        }
+    }
+
+    pub fn success(&mut self) {
+        self.failures = 0;
+        self.switch(CircuitState::Closed);
+    }
+
+    pub fn failure(&mut self) {
+        self.failures += 1;
+        let open = match self.state {
+            CircuitState::Closed => self.failures >= self.threshold,
+            CircuitState::HalfOpen => true,
+            CircuitState::Open => false,
+        };
+        if open {
+            self.opened_at = Instant::now();
+            self.switch(CircuitState::Open);
+        }
+    }
+
+    fn remained(&self) -> Option<Duration> {
+        if self.state == CircuitState::Open {
+            Some(self.cooldown.saturating_sub(self.opened_at.elapsed()))
+        } else {
+            None
+        }
+    }
+
+    fn switch(&mut self, to: CircuitState) {
+        let from = self.state;
+        if from != to {
+            self.state = to;
+            log::debug!("Circuit state changed from {from:?} to {to:?}");
+            for observer in &self.observers {
+                observer.send(CircuitChanged { from, to }).ok();
+            }
+        }
+    }
+}
+
+/// A state with repair policies.
+///
+/// The agent implements `DoAsync<S>` or `DoSync<S>` for the inner state only,
+/// failed iterations of `repeat` are delayed by the `Backoff` and
+/// the `CircuitBreaker` is checked and updated on every iteration.
+/// The `repair` method of the inner state is used if there is no backoff.
+///
+/// The default `handle`/`perform` loop is used for the wrapper,
+/// overridden loops of the inner state are not called.
+/// Delays of the policies end early when the agent is stopped.
+pub struct Resilient<S> {
+    pub state: S,
+    backoff: Option<Backoff>,
+    breaker: Option<CircuitBreaker>,
+}
+
+impl<S> Resilient<S> {
+    pub fn new(state: S) -> Self {
+        Self {
+            state,
+            backoff: None,
+            breaker: None,
+        }
+    }
+
+    pub fn with_backoff(mut self, backoff: Backoff) -> Self {
+        self.backoff = Some(backoff);
+        self
+    }
+
+    pub fn with_breaker(mut self, breaker: CircuitBreaker) -> Self {
+        self.breaker = Some(breaker);
+        self
+    }
+
+    fn stop_with(&mut self, stopper: Stopper) {
+        if let Some(backoff) = self.backoff.as_mut() {
+            backoff.stopper = Some(stopper.clone());
+        }
+        if let Some(breaker) = self.breaker.as_mut() {
+            breaker.stopper = Some(stopper);
+        }
+    }
+
+    /// The cooldown of the breaker has been interrupted.
+    fn is_open(&self) -> bool {
+        self.breaker
+            .as_ref()
+            .is_some_and(|breaker| breaker.state == CircuitState::Open)
+    }
+
+    fn succeeded(&mut self) {
+        if let Some(breaker) = self.breaker.as_mut() {
+            breaker.success();
+        }
+        if let Some(backoff) = self.backoff.as_mut() {
+            backoff.reset();
+        }
+    }
+
+    fn failed(&mut self) {
+        if let Some(breaker) = self.breaker.as_mut() {
+            breaker.failure();
+        }
+    }
+}
+
+#[async_trait]
+impl<A, S> DoAsync<Resilient<S>> for A
+where
+    A: DoAsync<S>,
+    S: AgentState,
+{
+    async fn handle(
+        &mut self,
+        mut state: Resilient<S>,
+        ctx: &mut Context<Self>,
+    ) -> Result<Next<Self>> {
+        let stopper = ctx.session().controller.stopper.clone();
+        state.stop_with(stopper.clone());
+        async_performer::repeat_while_active::<Self, Resilient<S>>(self, state, &stopper).await
+    }
+
+    async fn repeat(&mut self, state: &mut Resilient<S>) -> Result<Option<Next<Self>>> {
+        if let Some(breaker) = state.breaker.as_mut() {
+            breaker.ready().await;
+        }
+        if state.is_open() {
+            return Ok(None);
+        }
+        match DoAsync::<S>::repeat(self, &mut state.state).await {
+            Ok(next) => {
+                state.succeeded();
+                Ok(next)
+            }
+            Err(err) => {
+                state.failed();
+                match state.backoff.as_mut() {
+                    Some(backoff) => backoff.repair(err).await?,
+                    None => DoAsync::<S>::repair(self, err).await?,
+                }
+                Ok(None)
+            }
+        }
+    }
+
+    async fn fallback_with_context(&mut self, err: Error, ctx: &mut Context<Self>) -> Next<Self> {
+        DoAsync::<S>::fallback_with_context(self, err, ctx).await
+    }
+}
+
+#[cfg(feature = "sync")]
+impl<A, S> DoSync<Resilient<S>> for A
+where
+    A: DoSync<S>,
+    S: AgentState,
+{
+    fn perform(&mut self, mut state: Resilient<S>, stopper: Stopper) -> Result<Next<Self>> {
+        state.stop_with(stopper.clone());
+        sync_performer::repeat_while_active::<Self, Resilient<S>>(self, state, &stopper)
+    }
+
+    fn repeat(&mut self, state: &mut Resilient<S>) -> Result<Option<Next<Self>>> {
+        if let Some(breaker) = state.breaker.as_mut() {
+            breaker.ready_sync();
+        }
+        if state.is_open() {
+            return Ok(None);
+        }
+        match DoSync::<S>::repeat(self, &mut state.state) {
+            Ok(next) => {
+                state.succeeded();
+                Ok(next)
+            }
+            Err(err) => {
+                state.failed();
+                match state.backoff.as_mut() {
+                    Some(backoff) => backoff.repair_sync(err)?,
+                    None => DoSync::<S>::repair(self, err)?,
+                }
+                Ok(None)
+            }
+        }
+    }
+
+    fn fallback(&mut self, err: Error) -> Next<Self> {
+        DoSync::<S>::fallback(self, err)
+    }
+}
@@ -1,5 +1,6 @@
 use crate::agent::Agent;
 use crate::context::{AgentContext, Context};
+use crate::performers::nested_performer::leave_all;
 use crate::performers::{ConsumptionReason, StopReason, Transition, TransitionCommand};
 use anyhow::{anyhow, Result};
 use async_trait::async_trait;
@@ -70,8 +71,13 @@ impl<A: Agent> RunAgent<A> {
 
             // Events or States
             while self.context.is_alive() {
+                // Messages skipped by a canceled `receive`
+                self.context.session().stash.release_held();
                 let (mut agent, next_state) = pair;
                 if let Some(mut next_state) = next_state {
+                    if !next_state.nested {
+                        leave_all(&mut agent, &mut self.context);
+                    }
                     let res = next_state
                         .transition
                         .perform(agent, &mut self.context)
@@ -124,6 +130,7 @@ impl<A: Agent> RunAgent<A> {
 
             // Finalize
             let mut agent = pair.0;
+            leave_all(&mut agent, &mut self.context);
             agent.finalize(&mut self.context);
             self.agent = Some(agent);
             Ok(())
@@ -0,0 +1,124 @@
+//! A buffer for messages that an agent isn't ready to handle.
+
+use crate::address::{Envelope, MessageFor, WithHeaders};
+use crate::agent::Agent;
+use crate::context::{AgentContext, Context};
+use crate::message::event::{Event, OnEvent, TheEvent};
+use std::any::type_name;
+use std::collections::VecDeque;
+use thiserror::Error;
+
+/// The default number of messages that can be stashed.
+const DEFAULT_LIMIT: usize = 1_000;
+
+#[derive(Error, Debug)]
+#[error("The stash is full ({limit} messages)")]
+pub struct StashOverflow {
+    pub limit: usize,
+}
+
+/// Stashed messages of an agent.
+///
+/// Unstashed messages are handled before new messages from the mailbox.
+pub struct Stash<A: Agent> {
+    stashed: VecDeque<Envelope<A>>,
+    unstashed: VecDeque<Envelope<A>>,
+    /// Messages skipped by a pending `receive` call.
+    held: Vec<Envelope<A>>,
+    limit: usize,
+}
+
+impl<A: Agent> Default for Stash<A> {
+    fn default() -> Self {
+        Self {
+            stashed: VecDeque::new(),
+            unstashed: VecDeque::new(),
+            held: Vec::new(),
+            limit: DEFAULT_LIMIT,
+        }
+    }
+}
+
+impl<A: Agent> Stash<A> {
+    pub fn push(&mut self, envelope: Envelope<A>) -> Result<(), StashOverflow> {
+        if self.stashed.len() >= self.limit {
+            return Err(StashOverflow { limit: self.limit });
+        }
+        self.stashed.push_back(envelope);
+        Ok(())
+    }
+
+    pub fn unstash_all(&mut self) {
+        self.unstashed.append(&mut self.stashed);
+    }
+
+    pub fn set_limit(&mut self, limit: usize) {
+        self.limit = limit;
+    }
+
+    pub fn len(&self) -> usize {
+        self.stashed.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.stashed.is_empty()
+    }
+
+    /// Keeps a message skipped by `receive` out of the queue until it's released.
+    pub(crate) fn hold(&mut self, envelope: Envelope<A>) {
+        self.held.push(envelope);
+    }
+
+    /// Returns held messages to the front of the queue keeping their order.
+    ///
+    /// Called when `receive` completes or, if it was canceled, on the next step of the agent.
+    pub(crate) fn release_held(&mut self) {
+        for envelope in self.held.drain(..).rev() {
+            self.unstashed.push_front(envelope);
+        }
+    }
+
+    pub(crate) fn next_unstashed(&mut self) -> Option<Envelope<A>> {
+        self.unstashed.pop_front()
+    }
+
+    /// Takes all messages: held and unstashed first, then stashed.
+    pub(crate) fn drain(&mut self) -> Vec<Envelope<A>> {
+        self.release_held();
+        self.unstashed.append(&mut self.stashed);
+        self.unstashed.drain(..).collect()
+    }
+}
+
+impl<A: Agent> Context<A> {
+    /// Keeps the message to handle it after `unstash_all` is called.
+    ///
+    /// Headers of the message that is being handled are kept with it.
+    pub fn stash<M>(&mut self, msg: M) -> Result<(), StashOverflow>
+    where
+        M: MessageFor<A>,
+    {
+        let envelope: Envelope<A> = match self.headers.clone() {
+            Some(headers) => Box::new(WithHeaders {
+                headers,
+                message: type_name::<M>(),
+                envelope: Box::new(msg),
+            }),
+            None => Box::new(msg),
+        };
+        self.session().stash.push(envelope)
+    }
+
+    pub fn stash_event<E>(&mut self, event: E) -> Result<(), StashOverflow>
+    where
+        A: OnEvent<E>,
+        E: TheEvent,
+    {
+        self.stash(Event::new(event))
+    }
+
+    /// Returns all stashed messages to the front of the mailbox.
+    pub fn unstash_all(&mut self) {
+        self.session().stash.unstash_all();
+    }
+}
@@ -1,7 +1,7 @@
 use futures::stream::AbortHandle;
 
 // TODO: Use bit flags instead!
-#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
 pub struct InterruptionLevel(u32);
 
 impl InterruptionLevel {
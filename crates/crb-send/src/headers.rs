@@ -0,0 +1,71 @@
+//! Metadata that can be attached to a message.
+
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant, SystemTime};
+
+/// An optional header of a message.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Headers {
+    /// The name of the sender.
+    pub sender: Option<String>,
+    /// When the message was sent. It's set by a sender if it's empty.
+    pub timestamp: Option<SystemTime>,
+    /// An identifier that links messages of the same flow.
+    pub correlation_id: Option<String>,
+    /// When the message becomes outdated.
+    pub deadline: Option<Instant>,
+    /// Custom fields.
+    pub extra: BTreeMap<String, String>,
+}
+
+impl Headers {
+    /// Creates empty headers.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the name of the sender.
+    pub fn with_sender(mut self, sender: impl Into<String>) -> Self {
+        self.sender = Some(sender.into());
+        self
+    }
+
+    /// Sets the correlation id.
+    pub fn with_correlation_id(mut self, id: impl Into<String>) -> Self {
+        self.correlation_id = Some(id.into());
+        self
+    }
+
+    /// Sets the deadline.
+    pub fn with_deadline(mut self, deadline: Instant) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    /// Sets the deadline after the time to live from now.
+    pub fn with_ttl(self, ttl: Duration) -> Self {
+        self.with_deadline(Instant::now() + ttl)
+    }
+
+    /// Checks if the deadline has passed.
+    pub fn is_expired(&self) -> bool {
+        self.deadline
+            .is_some_and(|deadline| deadline <= Instant::now())
+    }
+
+    /// Adds a custom field.
+    pub fn with(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.extra.insert(key.into(), value.into());
+        self
+    }
+
+    /// Gets a custom field.
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.extra.get(key).map(String::as_str)
+    }
+
+    /// Sets the timestamp if it's not set yet.
+    pub fn stamp(&mut self) {
+        self.timestamp.get_or_insert_with(SystemTime::now);
+    }
+}
@@ -7,8 +7,10 @@
 //! two priority queues). If we use the `SendError` we have to
 //! drop the details!
 
+pub mod headers;
 pub mod notifier;
 pub mod sender;
 
+pub use headers::*;
 pub use notifier::*;
 pub use sender::*;
@@ -2,6 +2,7 @@
 //!
 //! The crate contains a trait and an implementation of a sender.
 
+use crate::headers::Headers;
 use crate::notifier::TypedNotifier;
 use anyhow::Result;
 use std::fmt;
@@ -12,6 +13,13 @@ pub trait Sender<M>: Send + Sync {
     /// Sends an event (data) to a recipient.
     fn send(&self, input: M) -> Result<()>;
 
+    /// Sends an event with headers.
+    ///
+    /// Senders that don't support headers drop them.
+    fn send_with(&self, input: M, _headers: Headers) -> Result<()> {
+        self.send(input)
+    }
+
     fn notifier(self, message: M) -> TypedNotifier<M>
     where
         Self: Sized + 'static,
@@ -62,6 +70,10 @@ impl<M> Sender<M> for Recipient<M> {
     fn send(&self, msg: M) -> Result<()> {
         self.recipient.send(msg)
     }
+
+    fn send_with(&self, msg: M, headers: Headers) -> Result<()> {
+        self.recipient.send_with(msg, headers)
+    }
 }
 
 impl<M> fmt::Debug for Recipient<M> {
@@ -88,11 +100,30 @@ impl<M> Recipient<M> {
         F: Send + Sync + 'static,
         M: 'static,
     {
-        let recipient = self.recipient.clone();
-        let func_sender = FuncSender(move |input| {
-            let output = func(input);
-            recipient.send(output)
-        });
-        Recipient::new(func_sender)
+        let reformed = Reformed {
+            recipient: self.recipient.clone(),
+            func,
+        };
+        Recipient::new(reformed)
+    }
+}
+
+/// A sender that converts messages and keeps their headers.
+struct Reformed<M, F> {
+    recipient: Arc<dyn Sender<M>>,
+    func: F,
+}
+
+impl<M, F, IN> Sender<IN> for Reformed<M, F>
+where
+    F: Fn(IN) -> M,
+    F: Send + Sync,
+{
+    fn send(&self, input: IN) -> Result<()> {
+        self.recipient.send((self.func)(input))
+    }
+
+    fn send_with(&self, input: IN, headers: Headers) -> Result<()> {
+        self.recipient.send_with((self.func)(input), headers)
     }
 }
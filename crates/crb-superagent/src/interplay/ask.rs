@@ -0,0 +1,216 @@
+use super::{
+    FetchError, Interaction, Interplay, OnRequest, OnResponse, Output, Request, Responder,
+};
+use anyhow::Result;
+use async_trait::async_trait;
+use crb_agent::extension::ExtensionFor;
+use crb_agent::{Address, Agent, Context, MessageFor};
+use crb_core::time::{sleep, Duration};
+use crb_core::{JoinHandle, Msg, Tag};
+use std::any::Any;
+use std::collections::HashMap;
+use std::marker::PhantomData;
+
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct AskId(u64);
+
+pub trait AskExt<A: Agent> {
+    /// Sends the request to the agent and routes the response
+    /// to the `OnResponse` handler of this agent.
+    fn ask<B, R, T>(&mut self, address: &Address<B>, request: R, tag: T) -> AskId
+    where
+        B: OnRequest<R>,
+        R: Request,
+        A: OnResponse<R::Response, T>,
+        T: Tag;
+
+    /// The same as `ask`, but with the specific timeout.
+    fn ask_within<B, R, T>(
+        &mut self,
+        address: &Address<B>,
+        request: R,
+        tag: T,
+        timeout: Duration,
+    ) -> AskId
+    where
+        B: OnRequest<R>,
+        R: Request,
+        A: OnResponse<R::Response, T>,
+        T: Tag;
+
+    /// Forgets the request. The response won't be delivered.
+    fn cancel_ask(&mut self, id: AskId) -> bool;
+
+    fn pending_asks(&mut self) -> usize;
+
+    /// Sets the timeout of requests sent with `ask`.
+    fn set_ask_timeout(&mut self, timeout: Duration);
+}
+
+impl<A: Agent> AskExt<A> for Context<A> {
+    fn ask<B, R, T>(&mut self, address: &Address<B>, request: R, tag: T) -> AskId
+    where
+        B: OnRequest<R>,
+        R: Request,
+        A: OnResponse<R::Response, T>,
+        T: Tag,
+    {
+        let timeout = self.asks().timeout;
+        self.ask_within(address, request, tag, timeout)
+    }
+
+    fn ask_within<B, R, T>(
+        &mut self,
+        address: &Address<B>,
+        request: R,
+        tag: T,
+        timeout: Duration,
+    ) -> AskId
+    where
+        B: OnRequest<R>,
+        R: Request,
+        A: OnResponse<R::Response, T>,
+        T: Tag,
+    {
+        let caller = self.address().clone();
+        let asks = self.asks();
+        let id = AskId(asks.next_id);
+        asks.next_id += 1;
+
+        let timer = {
+            let caller = caller.clone();
+            crb_core::spawn(async move {
+                sleep(timeout).await;
+                let response = Err(FetchError::TimedOut);
+                caller
+                    .send(AskResponse::<R::Response, T>::new(id, response))
+                    .ok();
+            })
+        };
+        let pending = Pending {
+            tag,
+            _timer: Timer(timer),
+        };
+        asks.pending.insert(id, Box::new(pending));
+
+        let responder = {
+            let caller = caller.clone();
+            Responder::from_fn(move |response: Result<R::Response>| {
+                let response = response.map_err(FetchError::from);
+                caller.send(AskResponse::<R::Response, T>::new(id, response))
+            })
+        };
+        let interplay = Interplay { request, responder };
+        if let Err(err) = address.send(Interaction { interplay }) {
+            let response = Err(FetchError::from(err));
+            caller
+                .send(AskResponse::<R::Response, T>::new(id, response))
+                .ok();
+        }
+        id
+    }
+
+    fn cancel_ask(&mut self, id: AskId) -> bool {
+        self.asks().pending.remove(&id).is_some()
+    }
+
+    fn pending_asks(&mut self) -> usize {
+        self.asks().pending.len()
+    }
+
+    fn set_ask_timeout(&mut self, timeout: Duration) {
+        self.asks().timeout = timeout;
+    }
+}
+
+trait AsksContext {
+    fn asks(&mut self) -> &mut Asks;
+}
+
+impl<A: Agent> AsksContext for Context<A> {
+    fn asks(&mut self) -> &mut Asks {
+        if self.be::<Asks>().is_err() {
+            self.add_extension(Asks::default());
+        }
+        self.be::<Asks>().expect("The extension has been added")
+    }
+}
+
+/// Pending requests of an agent.
+///
+/// Requests are canceled when the agent's context is dropped.
+struct Asks {
+    next_id: u64,
+    timeout: Duration,
+    pending: HashMap<AskId, Box<dyn Any + Send>>,
+}
+
+impl Default for Asks {
+    fn default() -> Self {
+        Self {
+            next_id: 0,
+            timeout: DEFAULT_TIMEOUT,
+            pending: HashMap::new(),
+        }
+    }
+}
+
+impl<A: Agent> ExtensionFor<A> for Asks {
+    type View<'a> = &'a mut Self;
+
+    fn extend(&mut self, _ctx: &mut A::Context) -> Self::View<'_> {
+        self
+    }
+}
+
+struct Pending<T> {
+    tag: T,
+    _timer: Timer,
+}
+
+/// Stops the timeout of a request when the request is forgotten.
+struct Timer(JoinHandle<()>);
+
+impl Drop for Timer {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
+struct AskResponse<OUT, T> {
+    id: AskId,
+    response: Output<OUT>,
+    _tag: PhantomData<fn() -> T>,
+}
+
+impl<OUT, T> AskResponse<OUT, T> {
+    fn new(id: AskId, response: Output<OUT>) -> Self {
+        Self {
+            id,
+            response,
+            _tag: PhantomData,
+        }
+    }
+}
+
+#[async_trait]
+impl<A, OUT, T> MessageFor<A> for AskResponse<OUT, T>
+where
+    A: OnResponse<OUT, T>,
+    OUT: Msg,
+    T: Tag,
+{
+    async fn handle(self: Box<Self>, agent: &mut A, ctx: &mut Context<A>) -> Result<()> {
+        // The request has been answered, timed out or canceled
+        let Some(pending) = ctx.asks().pending.remove(&self.id) else {
+            return Ok(());
+        };
+        let pending = pending
+            .downcast::<Pending<T>>()
+            .expect("The type of the tag is the same for the id");
+        let Pending { tag, .. } = *pending;
+        agent.on_response(self.response, tag, ctx).await
+    }
+}
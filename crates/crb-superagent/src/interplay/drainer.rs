@@ -65,6 +65,15 @@ pub struct DrainerTask<ITEM> {
     stream: Pin<Box<dyn Stream<Item = ITEM> + Send>>,
 }
 
+impl<ITEM> DrainerTask<ITEM> {
+    pub(crate) fn new(recipient: Recipient<ITEM>, drainer: Drainer<ITEM>) -> Self {
+        Self {
+            recipient,
+            stream: drainer.stream,
+        }
+    }
+}
+
 impl<ITEM> Agent for DrainerTask<ITEM>
 where
     ITEM: Msg,
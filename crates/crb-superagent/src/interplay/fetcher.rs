@@ -19,26 +19,60 @@ pub struct Interplay<IN, OUT> {
     pub responder: Responder<OUT>,
 }
 
+type Respond<OUT> = Box<dyn FnOnce(Result<OUT>) -> Result<()> + Send>;
+
+enum Responding<OUT> {
+    Channel(oneshot::Sender<Result<OUT>>),
+    Reformed(Respond<OUT>),
+}
+
 pub struct Responder<OUT> {
-    tx: oneshot::Sender<Result<OUT>>,
+    tx: Responding<OUT>,
 }
 
 impl<OUT> Responder<OUT> {
+    pub(crate) fn from_fn<F>(respond: F) -> Self
+    where
+        F: FnOnce(Result<OUT>) -> Result<()> + Send + 'static,
+    {
+        Self {
+            tx: Responding::Reformed(Box::new(respond)),
+        }
+    }
+
     pub fn send(self, resp: OUT) -> Result<()> {
         self.send_result(Ok(resp))
     }
 
     pub fn send_result(self, resp: Result<OUT>) -> Result<()> {
-        self.tx
-            .send(resp)
-            .map_err(|_| anyhow!("Can't send the response."))
+        match self.tx {
+            Responding::Channel(tx) => tx
+                .send(resp)
+                .map_err(|_| anyhow!("Can't send the response.")),
+            Responding::Reformed(respond) => respond(resp),
+        }
+    }
+
+    /// Changes `Responder` to another response type.
+    pub fn reform<F, IN>(self, func: F) -> Responder<IN>
+    where
+        F: FnOnce(IN) -> OUT,
+        F: Send + 'static,
+        OUT: Send + 'static,
+    {
+        let respond = Box::new(move |resp: Result<IN>| self.send_result(resp.map(func)));
+        Responder {
+            tx: Responding::Reformed(respond),
+        }
     }
 }
 
 impl<IN, OUT> Interplay<IN, OUT> {
     pub fn new_pair(request: IN) -> (Self, Fetcher<OUT>) {
         let (tx, rx) = oneshot::channel();
-        let responder = Responder { tx };
+        let responder = Responder {
+            tx: Responding::Channel(tx),
+        };
         let interplay = Interplay { request, responder };
         let fetcher = Fetcher { rx };
         (interplay, fetcher)
@@ -71,6 +105,8 @@ pub enum FetchError {
     Failed(#[from] anyhow::Error),
     #[error("Request canceled: {0}")]
     Canceled(#[from] Canceled),
+    #[error("Request timed out")]
+    TimedOut,
 }
 
 pub type Output<R> = Result<R, FetchError>;
@@ -159,9 +195,9 @@ pub trait OnResponse<OUT, T = ()>: Agent {
     ) -> Result<()>;
 }
 
-struct Response<OUT, T> {
-    response: Output<OUT>,
-    tag: T,
+pub(crate) struct Response<OUT, T> {
+    pub(crate) response: Output<OUT>,
+    pub(crate) tag: T,
 }
 
 #[async_trait]
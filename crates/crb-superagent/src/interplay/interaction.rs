@@ -2,9 +2,12 @@ use super::{Fetcher, Interplay};
 use anyhow::{anyhow, Result};
 use async_trait::async_trait;
 use crb_agent::{Address, Agent, Context, MessageFor};
+use crb_send::Headers;
 
 pub trait InteractExt<R: Request> {
     fn interact(&self, request: R) -> Fetcher<R::Response>;
+
+    fn interact_with(&self, request: R, headers: Headers) -> Fetcher<R::Response>;
 }
 
 impl<A, R> InteractExt<R> for Address<A>
@@ -18,6 +21,13 @@ where
         let res = self.send(msg);
         fetcher.grasp(res)
     }
+
+    fn interact_with(&self, request: R, headers: Headers) -> Fetcher<R::Response> {
+        let (interplay, fetcher) = Interplay::new_pair(request);
+        let msg = Interaction { interplay };
+        let res = self.send_with(msg, headers);
+        fetcher.grasp(res)
+    }
 }
 
 impl<A, R> InteractExt<R> for Context<A>
@@ -28,6 +38,10 @@ where
     fn interact(&self, request: R) -> Fetcher<R::Response> {
         self.address().interact(request)
     }
+
+    fn interact_with(&self, request: R, headers: Headers) -> Fetcher<R::Response> {
+        self.address().interact_with(request, headers)
+    }
 }
 
 pub trait Request: Send + 'static {
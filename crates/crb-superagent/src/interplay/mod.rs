@@ -1,11 +1,17 @@
+pub mod ask;
 pub mod drainer;
 pub mod fetcher;
 pub mod interaction;
 pub mod ping;
+pub mod requester;
+pub mod streaming;
 pub mod subscription;
 
+pub use ask::*;
 pub use drainer::*;
 pub use fetcher::*;
 pub use interaction::*;
 pub use ping::*;
+pub use requester::*;
+pub use streaming::*;
 pub use subscription::*;
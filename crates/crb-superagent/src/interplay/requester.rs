@@ -0,0 +1,107 @@
+use super::{Fetcher, Interaction, Interplay, OnRequest, Request};
+use anyhow::Result;
+use crb_agent::{Address, Context};
+use crb_send::{Recipient, Sender};
+use std::fmt;
+
+/// A universal cloneable requester that doesn't depend on a type of the agent.
+pub struct Requester<R: Request> {
+    recipient: Recipient<Interaction<R>>,
+}
+
+impl<R: Request> Clone for Requester<R> {
+    fn clone(&self) -> Self {
+        Self {
+            recipient: self.recipient.clone(),
+        }
+    }
+}
+
+impl<R: Request> fmt::Debug for Requester<R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Requester")
+    }
+}
+
+impl<R: Request> Requester<R> {
+    pub fn new(recipient: Recipient<Interaction<R>>) -> Self {
+        Self { recipient }
+    }
+
+    /// Creates a requester that responds with the function.
+    pub fn mock<F>(func: F) -> Self
+    where
+        F: Fn(R) -> Result<R::Response>,
+        F: Send + Sync + 'static,
+    {
+        let sender = MockSender { func };
+        Self::new(Recipient::new(sender))
+    }
+
+    pub fn request(&self, request: R) -> Fetcher<R::Response> {
+        let (interplay, fetcher) = Interplay::new_pair(request);
+        let msg = Interaction { interplay };
+        let res = self.recipient.send(msg);
+        fetcher.grasp(res)
+    }
+
+    /// Changes `Requester` to another request type.
+    pub fn reform<Q, F, G>(&self, into: F, from: G) -> Requester<Q>
+    where
+        Q: Request,
+        F: Fn(Q) -> R,
+        F: Send + Sync + 'static,
+        G: Fn(R::Response) -> Q::Response,
+        G: Clone + Send + Sync + 'static,
+    {
+        let recipient = self.recipient.reform(move |msg: Interaction<Q>| {
+            let Interplay { request, responder } = msg.interplay;
+            let interplay = Interplay {
+                request: into(request),
+                responder: responder.reform(from.clone()),
+            };
+            Interaction { interplay }
+        });
+        Requester::new(recipient)
+    }
+}
+
+struct MockSender<F> {
+    func: F,
+}
+
+impl<F, R> Sender<Interaction<R>> for MockSender<F>
+where
+    F: Fn(R) -> Result<R::Response>,
+    F: Send + Sync,
+    R: Request,
+{
+    fn send(&self, msg: Interaction<R>) -> Result<()> {
+        let Interplay { request, responder } = msg.interplay;
+        responder.send_result((self.func)(request))
+    }
+}
+
+pub trait ToRequester<R: Request> {
+    fn requester(&self) -> Requester<R>;
+}
+
+impl<A, R> ToRequester<R> for Address<A>
+where
+    A: OnRequest<R>,
+    R: Request,
+{
+    fn requester(&self) -> Requester<R> {
+        Requester::new(self.sender())
+    }
+}
+
+impl<A, R> ToRequester<R> for Context<A>
+where
+    A: OnRequest<R>,
+    R: Request,
+{
+    fn requester(&self) -> Requester<R> {
+        self.address().requester()
+    }
+}
@@ -0,0 +1,247 @@
+use super::drainer::{Drainer, DrainerTask};
+use crate::supervisor::ForwardTo;
+use anyhow::{anyhow, Error, Result};
+use async_trait::async_trait;
+use crb_agent::{Address, Agent, Context, MessageFor, RunAgent};
+use crb_core::{mpsc, Msg, Tag};
+use crb_runtime::InterruptionLevel;
+use futures::{
+    stream,
+    task::{Context as FutContext, Poll},
+    Stream, StreamExt,
+};
+use std::pin::Pin;
+
+pub trait StreamRequest: Send + 'static {
+    type Item: Send + 'static;
+}
+
+enum Chunk<ITEM> {
+    Item(ITEM),
+    End,
+    Failed(Error),
+}
+
+/// Sends many items as a response and finishes the stream with an end or an error.
+///
+/// If all responders are dropped without the end, the stream yields an error.
+pub struct StreamResponder<ITEM> {
+    tx: mpsc::UnboundedSender<Chunk<ITEM>>,
+}
+
+impl<ITEM> Clone for StreamResponder<ITEM> {
+    fn clone(&self) -> Self {
+        Self {
+            tx: self.tx.clone(),
+        }
+    }
+}
+
+impl<ITEM> StreamResponder<ITEM> {
+    pub fn send(&self, item: ITEM) -> Result<()> {
+        self.send_chunk(Chunk::Item(item))
+    }
+
+    pub fn end(self) -> Result<()> {
+        self.send_chunk(Chunk::End)
+    }
+
+    pub fn fail(self, err: Error) -> Result<()> {
+        self.send_chunk(Chunk::Failed(err))
+    }
+
+    /// Checks if the stream has been finished or dropped by the caller.
+    pub fn is_closed(&self) -> bool {
+        self.tx.is_closed()
+    }
+
+    fn send_chunk(&self, chunk: Chunk<ITEM>) -> Result<()> {
+        self.tx
+            .send(chunk)
+            .map_err(|_| anyhow!("Can't send the stream item."))
+    }
+}
+
+/// A stream of response items.
+#[must_use]
+pub struct ResponseStream<ITEM> {
+    rx: mpsc::UnboundedReceiver<Chunk<ITEM>>,
+    finished: bool,
+}
+
+impl<ITEM> ResponseStream<ITEM> {
+    pub fn new_pair() -> (StreamResponder<ITEM>, Self) {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let responder = StreamResponder { tx };
+        let stream = Self {
+            rx,
+            finished: false,
+        };
+        (responder, stream)
+    }
+
+    pub fn spoiled(err: Error) -> Self {
+        let (responder, stream) = Self::new_pair();
+        responder.fail(err).ok();
+        stream
+    }
+
+    pub fn grasp(self, result: Result<()>) -> Self {
+        match result {
+            Ok(_) => self,
+            Err(err) => Self::spoiled(err),
+        }
+    }
+}
+
+impl<ITEM> Stream for ResponseStream<ITEM> {
+    type Item = Result<ITEM>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut FutContext<'_>) -> Poll<Option<Self::Item>> {
+        if self.finished {
+            return Poll::Ready(None);
+        }
+        let poll = self.rx.poll_recv(cx).map(|chunk| match chunk {
+            Some(Chunk::Item(item)) => Some(Ok(item)),
+            Some(Chunk::End) => None,
+            Some(Chunk::Failed(err)) => Some(Err(err)),
+            None => Some(Err(anyhow!("The stream has been canceled."))),
+        });
+        if matches!(poll, Poll::Ready(None | Some(Err(_)))) {
+            // Closes the channel for all clones of the responder
+            self.finished = true;
+            self.rx.close();
+        }
+        poll
+    }
+}
+
+pub struct StreamInteraction<R: StreamRequest> {
+    pub request: R,
+    pub responder: StreamResponder<R::Item>,
+}
+
+#[async_trait]
+impl<A, R> MessageFor<A> for StreamInteraction<R>
+where
+    A: OnStreamRequest<R>,
+    R: StreamRequest,
+{
+    async fn handle(self: Box<Self>, agent: &mut A, ctx: &mut Context<A>) -> Result<()> {
+        agent.handle(*self, ctx).await
+    }
+}
+
+#[async_trait]
+pub trait OnStreamRequest<R: StreamRequest>: Agent {
+    async fn handle(&mut self, msg: StreamInteraction<R>, ctx: &mut Context<Self>) -> Result<()> {
+        let responder = msg.responder.clone();
+        if let Err(err) = self
+            .on_stream_request(msg.request, msg.responder, ctx)
+            .await
+        {
+            responder.fail(err).ok();
+        }
+        Ok(())
+    }
+
+    async fn on_stream_request(
+        &mut self,
+        _request: R,
+        _responder: StreamResponder<R::Item>,
+        _ctx: &mut Context<Self>,
+    ) -> Result<()> {
+        Err(anyhow!("The on_stream_request method in not implemented."))
+    }
+}
+
+pub trait StreamInteractExt<R: StreamRequest> {
+    fn interact_stream(&self, request: R) -> ResponseStream<R::Item>;
+}
+
+impl<A, R> StreamInteractExt<R> for Address<A>
+where
+    A: OnStreamRequest<R>,
+    R: StreamRequest,
+{
+    fn interact_stream(&self, request: R) -> ResponseStream<R::Item> {
+        let (responder, stream) = ResponseStream::new_pair();
+        let msg = StreamInteraction { request, responder };
+        let res = self.send(msg);
+        stream.grasp(res)
+    }
+}
+
+impl<A, R> StreamInteractExt<R> for Context<A>
+where
+    A: OnStreamRequest<R>,
+    R: StreamRequest,
+{
+    fn interact_stream(&self, request: R) -> ResponseStream<R::Item> {
+        self.address().interact_stream(request)
+    }
+}
+
+#[async_trait]
+pub trait OnStreamResponse<ITEM: Msg, T: Tag = ()>: Agent {
+    async fn on_item(&mut self, item: ITEM, tag: T, ctx: &mut Context<Self>) -> Result<()>;
+
+    /// Called when the stream is finished with the end or an error.
+    async fn on_end(
+        &mut self,
+        _result: Result<()>,
+        _tag: T,
+        _ctx: &mut Context<Self>,
+    ) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// An item or the end of a `ResponseStream` forwarded to an agent.
+pub struct StreamChunk<ITEM, T> {
+    chunk: Option<Result<ITEM>>,
+    tag: T,
+}
+
+#[async_trait]
+impl<A, ITEM, T> MessageFor<A> for StreamChunk<ITEM, T>
+where
+    A: OnStreamResponse<ITEM, T>,
+    ITEM: Msg,
+    T: Tag,
+{
+    async fn handle(self: Box<Self>, agent: &mut A, ctx: &mut Context<A>) -> Result<()> {
+        match self.chunk {
+            Some(Ok(item)) => agent.on_item(item, self.tag, ctx).await,
+            Some(Err(err)) => agent.on_end(Err(err), self.tag, ctx).await,
+            None => agent.on_end(Ok(()), self.tag, ctx).await,
+        }
+    }
+}
+
+impl<A, ITEM, T> ForwardTo<A, T> for ResponseStream<ITEM>
+where
+    A: OnStreamResponse<ITEM, T>,
+    ITEM: Msg,
+    T: Tag + Clone,
+{
+    type Runtime = RunAgent<DrainerTask<StreamChunk<ITEM, T>>>;
+
+    fn into_trackable(self, address: Address<A>, tag: T) -> Self::Runtime {
+        // Every item is forwarded with the tag, the end or an error finishes the stream
+        let chunks = stream::unfold(Some(self), move |stream| {
+            let tag = tag.clone();
+            async move {
+                let mut stream = stream?;
+                let chunk = stream.next().await;
+                let done = !matches!(chunk, Some(Ok(_)));
+                let rest = if done { None } else { Some(stream) };
+                Some((StreamChunk { chunk, tag }, rest))
+            }
+        });
+        let task = DrainerTask::new(address.sender(), Drainer::new(chunks));
+        let mut runtime = RunAgent::new(task);
+        runtime.level = InterruptionLevel::ABORT;
+        runtime
+    }
+}
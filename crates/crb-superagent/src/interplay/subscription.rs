@@ -1,12 +1,28 @@
 use super::{Fetcher, Interplay};
 use anyhow::{anyhow, Result};
 use async_trait::async_trait;
-use crb_agent::{Address, Agent, Context, MessageFor};
+use crb_agent::extension::ExtensionFor;
+use crb_agent::{Address, Agent, Context, MessageFor, ToRecipient};
 use crb_core::Unique;
 use crb_send::{Recipient, Sender};
+use futures::channel::mpsc;
+use futures::{
+    task::{Context as FutContext, Poll},
+    Stream, StreamExt,
+};
+use std::collections::HashMap;
+use std::pin::Pin;
 
 pub trait SubscribeExt<S: Subscription> {
+    /// Subscribes and provides updates as a stream of the `Entry`.
     fn subscribe(&self, request: S) -> Fetcher<StateEntry<S>>;
+
+    /// Subscribes and sends updates to the recipient.
+    fn subscribe_to(
+        &self,
+        request: S,
+        recipient: impl ToRecipient<S::Update>,
+    ) -> Fetcher<StateEntry<S>>;
 }
 
 impl<A, S> SubscribeExt<S> for Address<A>
@@ -15,14 +31,41 @@ where
     S: Subscription,
 {
     fn subscribe(&self, subscription: S) -> Fetcher<StateEntry<S>> {
-        let sub_id = Unique::new(subscription);
-        let (interplay, fetcher) = Interplay::new_pair(sub_id);
-        let msg = Subscribe { interplay };
-        let res = self.send(msg);
-        fetcher.grasp(res)
+        let (tx, rx) = mpsc::unbounded();
+        let listener = Recipient::new(UpdateSender { tx });
+        send_subscribe(self, subscription, listener, Some(rx))
+    }
+
+    fn subscribe_to(
+        &self,
+        subscription: S,
+        recipient: impl ToRecipient<S::Update>,
+    ) -> Fetcher<StateEntry<S>> {
+        send_subscribe(self, subscription, recipient.to_recipient(), None)
     }
 }
 
+fn send_subscribe<A, S>(
+    address: &Address<A>,
+    subscription: S,
+    listener: Recipient<S::Update>,
+    updates: Option<mpsc::UnboundedReceiver<S::Update>>,
+) -> Fetcher<StateEntry<S>>
+where
+    A: ManageSubscription<S>,
+    S: Subscription,
+{
+    let sub_id = Unique::new(subscription);
+    let (interplay, fetcher) = Interplay::new_pair(sub_id);
+    let msg = Subscribe {
+        interplay,
+        listener,
+        updates,
+    };
+    let res = address.send(msg);
+    fetcher.grasp(res)
+}
+
 impl<A, S> SubscribeExt<S> for Context<A>
 where
     A: ManageSubscription<S>,
@@ -31,6 +74,26 @@ where
     fn subscribe(&self, subscription: S) -> Fetcher<StateEntry<S>> {
         self.address().subscribe(subscription)
     }
+
+    fn subscribe_to(
+        &self,
+        subscription: S,
+        recipient: impl ToRecipient<S::Update>,
+    ) -> Fetcher<StateEntry<S>> {
+        self.address().subscribe_to(subscription, recipient)
+    }
+}
+
+struct UpdateSender<U> {
+    tx: mpsc::UnboundedSender<U>,
+}
+
+impl<U: Send> Sender<U> for UpdateSender<U> {
+    fn send(&self, update: U) -> Result<()> {
+        self.tx
+            .unbounded_send(update)
+            .map_err(|_| anyhow!("The subscriber has gone."))
+    }
 }
 
 #[must_use]
@@ -39,9 +102,24 @@ pub struct StateEntry<S: Subscription> {
     pub entry: Entry<S>,
 }
 
+/// A live subscription that is canceled when dropped.
+///
+/// If updates are not sent to a recipient, the entry yields them as a stream.
 pub struct Entry<S: Subscription> {
     sub_id: Unique<S>,
     recipient: Recipient<Unsubscribe<S>>,
+    updates: Option<mpsc::UnboundedReceiver<S::Update>>,
+}
+
+impl<S: Subscription> Stream for Entry<S> {
+    type Item = S::Update;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut FutContext<'_>) -> Poll<Option<Self::Item>> {
+        match self.updates.as_mut() {
+            Some(updates) => updates.poll_next_unpin(cx),
+            None => Poll::Ready(None),
+        }
+    }
 }
 
 impl<S: Subscription> Drop for Entry<S> {
@@ -55,6 +133,8 @@ impl<S: Subscription> Drop for Entry<S> {
 
 pub trait Subscription: Sync + Send + 'static {
     type State: Send + 'static;
+    /// Use `()` if the subscription has no updates.
+    type Update: Send + 'static;
 }
 
 #[async_trait]
@@ -64,8 +144,13 @@ pub trait ManageSubscription<S: Subscription>: Agent {
         let res = self.subscribe(sub_id.clone(), ctx).await;
         let state_entry = match res {
             Ok(state) => {
+                ctx.subscribers::<S>().insert(sub_id.clone(), msg.listener);
                 let recipient = ctx.address().sender();
-                let entry = Entry { sub_id, recipient };
+                let entry = Entry {
+                    sub_id,
+                    recipient,
+                    updates: msg.updates,
+                };
                 let state_entry = StateEntry { state, entry };
                 Ok(state_entry)
             }
@@ -89,6 +174,22 @@ pub trait ManageSubscription<S: Subscription>: Agent {
 
 pub struct Subscribe<S: Subscription> {
     pub interplay: Interplay<Unique<S>, StateEntry<S>>,
+    pub listener: Recipient<S::Update>,
+    updates: Option<mpsc::UnboundedReceiver<S::Update>>,
+}
+
+impl<S: Subscription> Subscribe<S> {
+    /// Creates a request that sends updates to the listener.
+    pub fn new(
+        interplay: Interplay<Unique<S>, StateEntry<S>>,
+        listener: Recipient<S::Update>,
+    ) -> Self {
+        Self {
+            interplay,
+            listener,
+            updates: None,
+        }
+    }
 }
 
 #[async_trait]
@@ -113,6 +214,92 @@ where
     S: Subscription,
 {
     async fn handle(self: Box<Self>, agent: &mut A, ctx: &mut Context<A>) -> Result<()> {
+        ctx.subscribers::<S>().remove(&self.sub_id);
         agent.unsubscribe(self.sub_id, ctx).await
     }
 }
+
+/// Update recipients of live subscribers.
+pub struct Subscribers<S: Subscription> {
+    listeners: HashMap<Unique<S>, Recipient<S::Update>>,
+}
+
+impl<A, S> ExtensionFor<A> for Subscribers<S>
+where
+    A: Agent,
+    S: Subscription,
+{
+    type View<'a> = &'a mut Self;
+
+    fn extend(&mut self, _ctx: &mut A::Context) -> Self::View<'_> {
+        self
+    }
+}
+
+impl<S: Subscription> Subscribers<S> {
+    fn insert(&mut self, sub_id: Unique<S>, listener: Recipient<S::Update>) {
+        self.listeners.insert(sub_id, listener);
+    }
+
+    fn remove(&mut self, sub_id: &Unique<S>) {
+        self.listeners.remove(sub_id);
+    }
+
+    pub fn len(&self) -> usize {
+        self.listeners.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.listeners.is_empty()
+    }
+
+    /// Sends the update to the subscriber.
+    pub fn update(&mut self, sub_id: &Unique<S>, update: S::Update) -> Result<()> {
+        let listener = self
+            .listeners
+            .get(sub_id)
+            .ok_or_else(|| anyhow!("The subscriber {sub_id} is not found."))?;
+        let res = listener.send(update);
+        if res.is_err() {
+            self.listeners.remove(sub_id);
+        }
+        res
+    }
+
+    /// Sends the update to all subscribers.
+    pub fn broadcast(&mut self, update: S::Update)
+    where
+        S::Update: Clone,
+    {
+        self.broadcast_with(|_| Some(update.clone()));
+    }
+
+    /// Sends personal updates produced from subscriptions.
+    /// Subscribers are skipped if there is no update for them.
+    pub fn broadcast_with<F>(&mut self, mut func: F)
+    where
+        F: FnMut(&S) -> Option<S::Update>,
+    {
+        self.listeners
+            .retain(|sub_id, listener| match func(sub_id) {
+                Some(update) => listener.send(update).is_ok(),
+                None => true,
+            });
+    }
+}
+
+pub trait SubscribersExt {
+    /// Live subscribers of the subscription type.
+    fn subscribers<S: Subscription>(&mut self) -> &mut Subscribers<S>;
+}
+
+impl<A: Agent> SubscribersExt for Context<A> {
+    fn subscribers<S: Subscription>(&mut self) -> &mut Subscribers<S> {
+        if self.be::<Subscribers<S>>().is_err() {
+            let listeners = HashMap::new();
+            self.add_extension(Subscribers::<S> { listeners });
+        }
+        self.be::<Subscribers<S>>()
+            .expect("The extension has been added")
+    }
+}
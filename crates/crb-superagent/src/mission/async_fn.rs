@@ -2,6 +2,7 @@ use super::{runtime::RunMission, Goal, Mission};
 use anyhow::Result;
 use async_trait::async_trait;
 use crb_agent::{Agent, AgentSession, Context, DoAsync, Next};
+use crb_runtime::InterruptionLevel;
 use futures::Future;
 
 impl<T: Goal> RunMission<AsyncFn<T>> {
@@ -10,7 +11,10 @@ impl<T: Goal> RunMission<AsyncFn<T>> {
             fut: Some(Box::new(fut)),
             output: None,
         };
-        Self::new(task)
+        let mut mission = Self::new(task);
+        // A future can't check the flag and has to be aborted
+        mission.runtime.level = InterruptionLevel::ABORT;
+        mission
     }
 }
 
@@ -0,0 +1,72 @@
+use super::Mission;
+use crb_agent::Address;
+use crb_runtime::{InterruptionLevel, Interruptor};
+use std::sync::{Arc, Mutex};
+
+/// A handle to cancel a running mission with a reason.
+pub struct CancelHandle<M: Mission> {
+    reason: Arc<Mutex<Option<(String, InterruptionLevel)>>>,
+    address: Address<M>,
+}
+
+impl<M: Mission> Clone for CancelHandle<M> {
+    fn clone(&self) -> Self {
+        Self {
+            reason: self.reason.clone(),
+            address: self.address.clone(),
+        }
+    }
+}
+
+impl<M: Mission> CancelHandle<M> {
+    pub(super) fn new(address: Address<M>) -> Self {
+        Self {
+            reason: Arc::new(Mutex::new(None)),
+            address,
+        }
+    }
+
+    /// Interrupts the mission that will deliver a partial goal.
+    pub fn cancel(&self, reason: impl ToString) {
+        self.cancel_with_level(reason, InterruptionLevel::FLAG);
+    }
+
+    pub fn cancel_with_level(&self, reason: impl ToString, level: InterruptionLevel) {
+        self.mark(reason.to_string(), level);
+        Interruptor::interrupt_with_level(&self.address, level);
+    }
+
+    pub fn is_canceled(&self) -> bool {
+        self.reason().is_some()
+    }
+
+    pub fn reason(&self) -> Option<String> {
+        self.cancellation().map(|(reason, _)| reason)
+    }
+
+    /// The level of the interruption that canceled the mission.
+    pub fn level(&self) -> Option<InterruptionLevel> {
+        self.cancellation().map(|(_, level)| level)
+    }
+
+    pub(super) fn cancellation(&self) -> Option<(String, InterruptionLevel)> {
+        self.reason.lock().ok().and_then(|reason| reason.clone())
+    }
+
+    fn mark(&self, reason: String, level: InterruptionLevel) {
+        if let Ok(mut current) = self.reason.lock() {
+            // Keeps the first reason only
+            current.get_or_insert((reason, level));
+        }
+    }
+}
+
+impl<M: Mission> Interruptor for CancelHandle<M> {
+    fn interrupt(&self) {
+        self.interrupt_with_level(InterruptionLevel::FLAG);
+    }
+
+    fn interrupt_with_level(&self, level: InterruptionLevel) {
+        self.cancel_with_level("Interrupted by the supervisor", level);
+    }
+}
@@ -0,0 +1,623 @@
+use super::{async_fn::AsyncFn, runtime::RunMission, Mission, MissionError};
+use crate::interplay::{OnResponse, Output};
+use crate::supervisor::{Supervisor, SupervisorSession};
+use anyhow::Result;
+use async_trait::async_trait;
+use crb_agent::{Agent, AgentSession, Context, Next};
+use crb_core::time::{sleep, Duration};
+use crb_runtime::ManagedContext;
+use std::any::type_name;
+use std::collections::VecDeque;
+
+fn delay(duration: Duration) -> RunMission<AsyncFn<()>> {
+    RunMission::new_async(sleep(duration))
+}
+
+/// Runs all missions concurrently and collects every goal.
+///
+/// If any of missions fails, the rest are interrupted.
+pub fn join_all<M>(missions: impl IntoIterator<Item = RunMission<M>>) -> RunMission<JoinAll<M>>
+where
+    M: Mission,
+{
+    let join = JoinAll {
+        missions: missions.into_iter().collect(),
+        goals: Vec::new(),
+        failed: false,
+    };
+    RunMission::new(join)
+}
+
+pub struct JoinAll<M: Mission> {
+    missions: Vec<RunMission<M>>,
+    goals: Vec<Option<M::Goal>>,
+    failed: bool,
+}
+
+impl<M: Mission> Supervisor for JoinAll<M> {
+    type BasedOn = AgentSession<Self>;
+    type GroupBy = ();
+}
+
+impl<M: Mission> Agent for JoinAll<M> {
+    type Context = SupervisorSession<Self>;
+
+    fn initialize(&mut self, ctx: &mut Context<Self>) -> Next<Self> {
+        for (index, mission) in self.missions.drain(..).enumerate() {
+            self.goals.push(None);
+            ctx.assign(mission, (), index);
+        }
+        if self.goals.is_empty() {
+            ctx.shutdown();
+        }
+        Next::events()
+    }
+}
+
+#[async_trait]
+impl<M: Mission> OnResponse<M::Goal, usize> for JoinAll<M> {
+    async fn on_response(
+        &mut self,
+        response: Output<M::Goal>,
+        index: usize,
+        ctx: &mut Context<Self>,
+    ) -> Result<()> {
+        match response {
+            Ok(goal) => {
+                self.goals[index] = Some(goal);
+                if self.goals.iter().all(Option::is_some) {
+                    ctx.shutdown();
+                }
+                Ok(())
+            }
+            Err(err) => {
+                self.failed = true;
+                ctx.shutdown();
+                Err(err.into())
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl<M: Mission> Mission for JoinAll<M> {
+    type Goal = Vec<M::Goal>;
+
+    async fn deliver(self, _ctx: &mut Context<Self>) -> Option<Self::Goal> {
+        if self.failed {
+            None
+        } else {
+            self.goals.into_iter().collect()
+        }
+    }
+}
+
+/// Runs all missions concurrently and takes the first reached goal.
+///
+/// Other missions are interrupted once the goal is reached.
+pub fn race<M>(missions: impl IntoIterator<Item = RunMission<M>>) -> RunMission<Race<M>>
+where
+    M: Mission,
+{
+    let race = Race {
+        missions: missions.into_iter().collect(),
+        remained: 0,
+        goal: None,
+    };
+    RunMission::new(race)
+}
+
+pub struct Race<M: Mission> {
+    missions: Vec<RunMission<M>>,
+    remained: usize,
+    goal: Option<M::Goal>,
+}
+
+impl<M: Mission> Supervisor for Race<M> {
+    type BasedOn = AgentSession<Self>;
+    type GroupBy = ();
+}
+
+impl<M: Mission> Agent for Race<M> {
+    type Context = SupervisorSession<Self>;
+
+    fn initialize(&mut self, ctx: &mut Context<Self>) -> Next<Self> {
+        for mission in self.missions.drain(..) {
+            self.remained += 1;
+            ctx.assign(mission, (), ());
+        }
+        if self.remained == 0 {
+            ctx.shutdown();
+        }
+        Next::events()
+    }
+}
+
+#[async_trait]
+impl<M: Mission> OnResponse<M::Goal> for Race<M> {
+    async fn on_response(
+        &mut self,
+        response: Output<M::Goal>,
+        _tag: (),
+        ctx: &mut Context<Self>,
+    ) -> Result<()> {
+        self.remained -= 1;
+        match response {
+            Ok(goal) if self.goal.is_none() => {
+                self.goal = Some(goal);
+                ctx.shutdown();
+            }
+            Ok(_) => {
+                // The race has a winner already
+            }
+            Err(err) => {
+                log::debug!("Mission of {} failed: {err}", type_name::<Self>());
+                if self.remained == 0 {
+                    ctx.shutdown();
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl<M: Mission> Mission for Race<M> {
+    type Goal = M::Goal;
+
+    async fn deliver(self, _ctx: &mut Context<Self>) -> Option<Self::Goal> {
+        self.goal
+    }
+}
+
+/// Runs missions one by one and collects their goals.
+///
+/// The sequence stops on the first failed mission.
+pub fn sequence<M>(missions: impl IntoIterator<Item = RunMission<M>>) -> RunMission<Sequence<M>>
+where
+    M: Mission,
+{
+    let sequence = Sequence {
+        missions: missions.into_iter().collect(),
+        goals: Vec::new(),
+        failed: false,
+    };
+    RunMission::new(sequence)
+}
+
+pub struct Sequence<M: Mission> {
+    missions: VecDeque<RunMission<M>>,
+    goals: Vec<M::Goal>,
+    failed: bool,
+}
+
+impl<M: Mission> Sequence<M> {
+    fn next(&mut self, ctx: &mut Context<Self>) {
+        if let Some(mission) = self.missions.pop_front() {
+            ctx.assign(mission, (), ());
+        } else {
+            ctx.shutdown();
+        }
+    }
+}
+
+impl<M: Mission> Supervisor for Sequence<M> {
+    type BasedOn = AgentSession<Self>;
+    type GroupBy = ();
+}
+
+impl<M: Mission> Agent for Sequence<M> {
+    type Context = SupervisorSession<Self>;
+
+    fn initialize(&mut self, ctx: &mut Context<Self>) -> Next<Self> {
+        self.next(ctx);
+        Next::events()
+    }
+}
+
+#[async_trait]
+impl<M: Mission> OnResponse<M::Goal> for Sequence<M> {
+    async fn on_response(
+        &mut self,
+        response: Output<M::Goal>,
+        _tag: (),
+        ctx: &mut Context<Self>,
+    ) -> Result<()> {
+        match response {
+            Ok(goal) => {
+                self.goals.push(goal);
+                self.next(ctx);
+                Ok(())
+            }
+            Err(err) => {
+                self.failed = true;
+                ctx.shutdown();
+                Err(err.into())
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl<M: Mission> Mission for Sequence<M> {
+    type Goal = Vec<M::Goal>;
+
+    async fn deliver(self, _ctx: &mut Context<Self>) -> Option<Self::Goal> {
+        if self.failed {
+            None
+        } else {
+            Some(self.goals)
+        }
+    }
+}
+
+impl<M: Mission> RunMission<M> {
+    /// Feeds the goal of the mission into the next one.
+    pub fn and_then<N, F>(self, func: F) -> RunMission<AndThen<M, N, F>>
+    where
+        N: Mission,
+        F: FnOnce(M::Goal) -> RunMission<N>,
+        F: Send + 'static,
+    {
+        let and_then = AndThen {
+            first: Some(self),
+            func: Some(func),
+            goal: None,
+        };
+        RunMission::new(and_then)
+    }
+}
+
+/// A tag of the first mission of `AndThen`.
+pub struct First;
+
+/// A tag of the second mission of `AndThen`.
+pub struct Then;
+
+pub struct AndThen<M: Mission, N: Mission, F> {
+    first: Option<RunMission<M>>,
+    func: Option<F>,
+    goal: Option<N::Goal>,
+}
+
+impl<M, N, F> Supervisor for AndThen<M, N, F>
+where
+    M: Mission,
+    N: Mission,
+    F: FnOnce(M::Goal) -> RunMission<N>,
+    F: Send + 'static,
+{
+    type BasedOn = AgentSession<Self>;
+    type GroupBy = ();
+}
+
+impl<M, N, F> Agent for AndThen<M, N, F>
+where
+    M: Mission,
+    N: Mission,
+    F: FnOnce(M::Goal) -> RunMission<N>,
+    F: Send + 'static,
+{
+    type Context = SupervisorSession<Self>;
+
+    fn initialize(&mut self, ctx: &mut Context<Self>) -> Next<Self> {
+        if let Some(mission) = self.first.take() {
+            ctx.assign(mission, (), First);
+        }
+        Next::events()
+    }
+}
+
+#[async_trait]
+impl<M, N, F> OnResponse<M::Goal, First> for AndThen<M, N, F>
+where
+    M: Mission,
+    N: Mission,
+    F: FnOnce(M::Goal) -> RunMission<N>,
+    F: Send + 'static,
+{
+    async fn on_response(
+        &mut self,
+        response: Output<M::Goal>,
+        _tag: First,
+        ctx: &mut Context<Self>,
+    ) -> Result<()> {
+        match (response, self.func.take()) {
+            (Ok(goal), Some(func)) => {
+                let mission = func(goal);
+                ctx.assign(mission, (), Then);
+                Ok(())
+            }
+            (Ok(_), None) => {
+                ctx.shutdown();
+                Ok(())
+            }
+            (Err(err), _) => {
+                ctx.shutdown();
+                Err(err.into())
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl<M, N, F> OnResponse<N::Goal, Then> for AndThen<M, N, F>
+where
+    M: Mission,
+    N: Mission,
+    F: FnOnce(M::Goal) -> RunMission<N>,
+    F: Send + 'static,
+{
+    async fn on_response(
+        &mut self,
+        response: Output<N::Goal>,
+        _tag: Then,
+        ctx: &mut Context<Self>,
+    ) -> Result<()> {
+        ctx.shutdown();
+        self.goal = Some(response?);
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl<M, N, F> Mission for AndThen<M, N, F>
+where
+    M: Mission,
+    N: Mission,
+    F: FnOnce(M::Goal) -> RunMission<N>,
+    F: Send + 'static,
+{
+    type Goal = N::Goal;
+
+    async fn deliver(self, _ctx: &mut Context<Self>) -> Option<Self::Goal> {
+        self.goal
+    }
+}
+
+/// Rules of re-running a failed mission.
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    pub max_attempts: usize,
+    pub delay: Option<Duration>,
+}
+
+impl RetryPolicy {
+    pub fn attempts(max_attempts: usize) -> Self {
+        Self {
+            max_attempts,
+            delay: None,
+        }
+    }
+
+    pub fn with_delay(mut self, delay: Duration) -> Self {
+        self.delay = Some(delay);
+        self
+    }
+}
+
+/// Re-runs a mission created by the factory until it reaches the goal
+/// or the attempts of the policy are exhausted.
+pub fn retry<M, F>(policy: RetryPolicy, factory: F) -> RunMission<Retry<M, F>>
+where
+    M: Mission,
+    F: Fn() -> RunMission<M>,
+    F: Send + 'static,
+{
+    let retry = Retry {
+        policy,
+        factory,
+        attempt: 0,
+        goal: None,
+    };
+    RunMission::new(retry)
+}
+
+/// A tag of a delay between attempts of `Retry`.
+pub struct Attempt;
+
+pub struct Retry<M: Mission, F> {
+    policy: RetryPolicy,
+    factory: F,
+    attempt: usize,
+    goal: Option<M::Goal>,
+}
+
+impl<M, F> Retry<M, F>
+where
+    M: Mission,
+    F: Fn() -> RunMission<M>,
+    F: Send + 'static,
+{
+    fn attempt(&mut self, ctx: &mut Context<Self>) {
+        self.attempt += 1;
+        let mission = (self.factory)();
+        ctx.assign(mission, (), ());
+    }
+}
+
+impl<M, F> Supervisor for Retry<M, F>
+where
+    M: Mission,
+    F: Fn() -> RunMission<M>,
+    F: Send + 'static,
+{
+    type BasedOn = AgentSession<Self>;
+    type GroupBy = ();
+}
+
+impl<M, F> Agent for Retry<M, F>
+where
+    M: Mission,
+    F: Fn() -> RunMission<M>,
+    F: Send + 'static,
+{
+    type Context = SupervisorSession<Self>;
+
+    fn initialize(&mut self, ctx: &mut Context<Self>) -> Next<Self> {
+        self.attempt(ctx);
+        Next::events()
+    }
+}
+
+#[async_trait]
+impl<M, F> OnResponse<M::Goal> for Retry<M, F>
+where
+    M: Mission,
+    F: Fn() -> RunMission<M>,
+    F: Send + 'static,
+{
+    async fn on_response(
+        &mut self,
+        response: Output<M::Goal>,
+        _tag: (),
+        ctx: &mut Context<Self>,
+    ) -> Result<()> {
+        match response {
+            Ok(goal) => {
+                self.goal = Some(goal);
+                ctx.shutdown();
+            }
+            Err(err) if self.attempt < self.policy.max_attempts => {
+                log::debug!(
+                    "Attempt {} of {} failed: {err}",
+                    self.attempt,
+                    type_name::<Self>()
+                );
+                if let Some(duration) = self.policy.delay {
+                    ctx.assign(delay(duration), (), Attempt);
+                } else {
+                    self.attempt(ctx);
+                }
+            }
+            Err(err) => {
+                ctx.shutdown();
+                return Err(err.into());
+            }
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl<M, F> OnResponse<(), Attempt> for Retry<M, F>
+where
+    M: Mission,
+    F: Fn() -> RunMission<M>,
+    F: Send + 'static,
+{
+    async fn on_response(
+        &mut self,
+        _response: Output<()>,
+        _tag: Attempt,
+        ctx: &mut Context<Self>,
+    ) -> Result<()> {
+        self.attempt(ctx);
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl<M, F> Mission for Retry<M, F>
+where
+    M: Mission,
+    F: Fn() -> RunMission<M>,
+    F: Send + 'static,
+{
+    type Goal = M::Goal;
+
+    async fn deliver(self, _ctx: &mut Context<Self>) -> Option<Self::Goal> {
+        self.goal
+    }
+}
+
+/// Interrupts a mission if it hasn't reached the goal in time.
+pub fn timeout<M>(duration: Duration, mission: RunMission<M>) -> RunMission<Timeout<M>>
+where
+    M: Mission,
+{
+    let timeout = Timeout {
+        duration,
+        mission: Some(mission),
+        elapsed: false,
+        goal: None,
+    };
+    RunMission::new(timeout)
+}
+
+/// A tag of the deadline of `Timeout`.
+pub struct Elapsed;
+
+pub struct Timeout<M: Mission> {
+    duration: Duration,
+    mission: Option<RunMission<M>>,
+    elapsed: bool,
+    goal: Option<M::Goal>,
+}
+
+impl<M: Mission> Supervisor for Timeout<M> {
+    type BasedOn = AgentSession<Self>;
+    type GroupBy = ();
+}
+
+impl<M: Mission> Agent for Timeout<M> {
+    type Context = SupervisorSession<Self>;
+
+    fn initialize(&mut self, ctx: &mut Context<Self>) -> Next<Self> {
+        if let Some(mission) = self.mission.take() {
+            ctx.assign(mission, (), ());
+            ctx.assign(delay(self.duration), (), Elapsed);
+        }
+        Next::events()
+    }
+}
+
+#[async_trait]
+impl<M: Mission> OnResponse<M::Goal> for Timeout<M> {
+    async fn on_response(
+        &mut self,
+        response: Output<M::Goal>,
+        _tag: (),
+        ctx: &mut Context<Self>,
+    ) -> Result<()> {
+        ctx.shutdown();
+        if !self.elapsed {
+            self.goal = Some(response?);
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl<M: Mission> OnResponse<(), Elapsed> for Timeout<M> {
+    async fn on_response(
+        &mut self,
+        response: Output<()>,
+        _tag: Elapsed,
+        ctx: &mut Context<Self>,
+    ) -> Result<()> {
+        if response.is_ok() && self.goal.is_none() {
+            log::debug!("Mission of {} has timed out", type_name::<Self>());
+            self.elapsed = true;
+            ctx.shutdown();
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl<M: Mission> Mission for Timeout<M> {
+    type Goal = M::Goal;
+
+    async fn deliver(self, _ctx: &mut Context<Self>) -> Option<Self::Goal> {
+        self.goal
+    }
+
+    fn no_goal(&self) -> MissionError {
+        if self.elapsed {
+            MissionError::TimedOut
+        } else {
+            MissionError::NoGoal
+        }
+    }
+}
@@ -0,0 +1,59 @@
+use super::{runtime::RunMission, Mission};
+use crate::interplay::{FetchError, OnResponse, Response};
+use crate::supervisor::ForwardTo;
+use anyhow::Error;
+use async_trait::async_trait;
+use crb_agent::Address;
+use crb_core::{Slot, Tag};
+use crb_runtime::{InterruptionLevel, Interruptor, Runtime};
+use crb_send::{Recipient, Sender};
+
+impl<A, M, T> ForwardTo<A, T> for RunMission<M>
+where
+    A: OnResponse<M::Goal, T>,
+    M: Mission,
+    T: Tag,
+{
+    type Runtime = GoalForwarder<M, T>;
+
+    fn into_trackable(self, address: Address<A>, tag: T) -> Self::Runtime {
+        GoalForwarder {
+            mission: self,
+            recipient: address.sender(),
+            tag: Slot::filled(tag),
+        }
+    }
+}
+
+/// Runs a mission and sends its goal to an agent.
+pub struct GoalForwarder<M: Mission, T> {
+    mission: RunMission<M>,
+    recipient: Recipient<Response<M::Goal, T>>,
+    tag: Slot<T>,
+}
+
+#[async_trait]
+impl<M, T> Runtime for GoalForwarder<M, T>
+where
+    M: Mission,
+    T: Tag,
+{
+    fn get_interruptor(&mut self) -> Box<dyn Interruptor> {
+        self.mission.get_interruptor()
+    }
+
+    fn interruption_level(&self) -> InterruptionLevel {
+        self.mission.interruption_level()
+    }
+
+    async fn routine(&mut self) {
+        let response = self
+            .mission
+            .perform()
+            .await
+            .map_err(|err| FetchError::Failed(Error::from(err)));
+        if let Ok(tag) = self.tag.take() {
+            self.recipient.send(Response { response, tag }).ok();
+        }
+    }
+}
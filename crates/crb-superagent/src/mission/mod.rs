@@ -1,9 +1,18 @@
 pub mod async_fn;
+pub mod cancel;
+pub mod combinators;
+pub mod forwarding;
+pub mod progress;
+pub mod queue;
 pub mod reporting;
 pub mod runtime;
 pub mod sync_fn;
 
-pub use runtime::RunMission;
+pub use cancel::CancelHandle;
+pub use combinators::{join_all, race, retry, sequence, timeout, RetryPolicy};
+pub use progress::{Progress, ProgressExt};
+pub use queue::{Job, JobId, JobQueue, JobQueueExt};
+pub use runtime::{MissionError, RunMission};
 
 use anyhow::Result;
 use async_trait::async_trait;
@@ -18,6 +27,17 @@ pub trait Mission: Agent {
     type Goal: Goal;
 
     async fn deliver(self, ctx: &mut Context<Self>) -> Option<Self::Goal>;
+
+    /// Called instead of `deliver` if the mission has been canceled
+    /// to keep results that were achieved before the interruption.
+    async fn deliver_partial(self, _ctx: &mut Context<Self>) -> Option<Self::Goal> {
+        None
+    }
+
+    /// The error that is returned if `deliver` has no goal.
+    fn no_goal(&self) -> MissionError {
+        MissionError::NoGoal
+    }
 }
 
 pub trait Observer<M: Mission>: Send {
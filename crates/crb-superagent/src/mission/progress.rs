@@ -0,0 +1,66 @@
+use super::runtime::RunMission;
+use super::Mission;
+use crb_agent::extension::ExtensionFor;
+use crb_agent::{Agent, Context, OnEvent, ToAddress};
+use crb_send::{Recipient, Sender};
+
+/// An intermediate result of a mission.
+#[derive(Debug, Clone)]
+pub struct Progress<P> {
+    pub value: P,
+}
+
+impl<M: Mission> RunMission<M> {
+    pub fn report_progress_to<P, S>(&mut self, address: impl ToAddress<S>)
+    where
+        S: OnEvent<Progress<P>>,
+        P: Clone + Send + 'static,
+    {
+        let recipient = address.to_address().recipient();
+        let context = &mut self.runtime.context;
+        if let Ok(tracker) = context.be::<ProgressTracker<P>>() {
+            tracker.recipients.push(recipient);
+        } else {
+            let recipients = vec![recipient];
+            context.add_extension(ProgressTracker { recipients });
+        }
+    }
+}
+
+pub struct ProgressTracker<P> {
+    recipients: Vec<Recipient<Progress<P>>>,
+}
+
+impl<A, P> ExtensionFor<A> for ProgressTracker<P>
+where
+    A: Agent,
+    P: Send + 'static,
+{
+    type View<'a> = &'a mut Self;
+
+    fn extend(&mut self, _ctx: &mut A::Context) -> Self::View<'_> {
+        self
+    }
+}
+
+pub trait ProgressExt<P> {
+    /// Sends the progress to all observers of the mission.
+    fn progress(&mut self, value: P);
+}
+
+impl<A, P> ProgressExt<P> for Context<A>
+where
+    A: Agent,
+    P: Clone + Send + 'static,
+{
+    fn progress(&mut self, value: P) {
+        if let Ok(tracker) = self.be::<ProgressTracker<P>>() {
+            for recipient in &tracker.recipients {
+                let progress = Progress {
+                    value: value.clone(),
+                };
+                recipient.send(progress).ok();
+            }
+        }
+    }
+}
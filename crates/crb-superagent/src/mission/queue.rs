@@ -0,0 +1,252 @@
+use super::{runtime::RunMission, CancelHandle, Mission};
+use crate::interplay::{Fetcher, Interplay, OnResponse, Output, Responder};
+use crate::supervisor::{Supervisor, SupervisorSession};
+use anyhow::{anyhow, Error, Result};
+use async_trait::async_trait;
+use crb_agent::{Address, Agent, AgentSession, Context, MessageFor, Standalone};
+use crb_core::Unique;
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap};
+
+/// An identifier of a submitted job.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct JobId(Unique);
+
+/// A mission with a priority to run in a `JobQueue`.
+pub struct Job<M: Mission> {
+    id: JobId,
+    priority: i32,
+    mission: RunMission<M>,
+}
+
+impl<M: Mission> Job<M> {
+    pub fn new(mission: RunMission<M>) -> Self {
+        Self {
+            id: JobId(Unique::default()),
+            priority: 0,
+            mission,
+        }
+    }
+
+    /// Jobs with a higher priority are started first.
+    pub fn with_priority(mut self, priority: i32) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    pub fn id(&self) -> JobId {
+        self.id.clone()
+    }
+}
+
+impl<M: Mission> From<RunMission<M>> for Job<M> {
+    fn from(mission: RunMission<M>) -> Self {
+        Self::new(mission)
+    }
+}
+
+/// Runs submitted missions with a limited concurrency.
+///
+/// Jobs are started in the order of priorities, equal priorities keep the order of submission.
+pub struct JobQueue<M: Mission> {
+    limit: usize,
+    counter: u64,
+    queued: BinaryHeap<Queued<M>>,
+    running: HashMap<JobId, Running<M>>,
+}
+
+impl<M: Mission> JobQueue<M> {
+    /// Creates a queue that runs up to `limit` jobs at once.
+    ///
+    /// A queue can't be paused, so a `limit` of `0` is raised to `1`.
+    pub fn new(limit: usize) -> Self {
+        Self {
+            limit: limit.max(1),
+            counter: 0,
+            queued: BinaryHeap::new(),
+            running: HashMap::new(),
+        }
+    }
+
+    fn enqueue(&mut self, job: Job<M>, responder: Responder<M::Goal>) {
+        self.counter += 1;
+        let queued = Queued {
+            order: self.counter,
+            job,
+            responder,
+        };
+        self.queued.push(queued);
+    }
+
+    fn cancel(&mut self, id: JobId) {
+        if let Some(running) = self.running.get_mut(&id) {
+            running.canceled = true;
+            running.cancel.cancel("Job canceled");
+            return;
+        }
+        let mut queued = std::mem::take(&mut self.queued).into_vec();
+        if let Some(pos) = queued.iter().position(|queued| queued.job.id == id) {
+            let canceled = queued.swap_remove(pos);
+            canceled
+                .responder
+                .send_result(Err(anyhow!("Job canceled")))
+                .ok();
+        }
+        self.queued = queued.into();
+    }
+
+    fn dispatch(&mut self, ctx: &mut Context<Self>) {
+        if ctx.tracker.is_terminating() {
+            // New jobs can't be started by a terminating queue
+            for queued in self.queued.drain() {
+                queued
+                    .responder
+                    .send_result(Err(anyhow!("Job queue terminated")))
+                    .ok();
+            }
+            return;
+        }
+        while self.running.len() < self.limit {
+            let Some(Queued { job, responder, .. }) = self.queued.pop() else {
+                break;
+            };
+            let cancel = job.mission.cancel_handle();
+            let id = job.id;
+            ctx.assign(job.mission, (), id.clone());
+            let running = Running {
+                cancel,
+                responder,
+                canceled: false,
+            };
+            self.running.insert(id, running);
+        }
+    }
+}
+
+impl<M: Mission> Standalone for JobQueue<M> {}
+
+impl<M: Mission> Supervisor for JobQueue<M> {
+    type BasedOn = AgentSession<Self>;
+    type GroupBy = ();
+}
+
+impl<M: Mission> Agent for JobQueue<M> {
+    type Context = SupervisorSession<Self>;
+}
+
+#[async_trait]
+impl<M: Mission> OnResponse<M::Goal, JobId> for JobQueue<M> {
+    async fn on_response(
+        &mut self,
+        response: Output<M::Goal>,
+        id: JobId,
+        ctx: &mut Context<Self>,
+    ) -> Result<()> {
+        if let Some(running) = self.running.remove(&id) {
+            let response = if running.canceled {
+                // A partial goal of a canceled job is not a result
+                Err(anyhow!("Job canceled"))
+            } else if ctx.tracker.is_terminating() {
+                Err(anyhow!("Job queue terminated"))
+            } else {
+                response.map_err(Error::from)
+            };
+            running.responder.send_result(response).ok();
+        }
+        self.dispatch(ctx);
+        Ok(())
+    }
+}
+
+struct Queued<M: Mission> {
+    order: u64,
+    job: Job<M>,
+    responder: Responder<M::Goal>,
+}
+
+impl<M: Mission> PartialEq for Queued<M> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<M: Mission> Eq for Queued<M> {}
+
+impl<M: Mission> PartialOrd for Queued<M> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<M: Mission> Ord for Queued<M> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.job
+            .priority
+            .cmp(&other.job.priority)
+            .then_with(|| other.order.cmp(&self.order))
+    }
+}
+
+struct Running<M: Mission> {
+    cancel: CancelHandle<M>,
+    responder: Responder<M::Goal>,
+    canceled: bool,
+}
+
+pub trait JobQueueExt<M: Mission> {
+    /// Adds a job to the queue and returns a fetcher of its goal.
+    fn submit(&self, job: impl Into<Job<M>>) -> Fetcher<M::Goal>;
+
+    /// Removes a queued job or cancels a running one.
+    fn cancel_job(&self, id: JobId) -> Result<()>;
+}
+
+impl<M: Mission> JobQueueExt<M> for Address<JobQueue<M>> {
+    fn submit(&self, job: impl Into<Job<M>>) -> Fetcher<M::Goal> {
+        let (interplay, fetcher) = Interplay::new_pair(());
+        let msg = Submit {
+            job: job.into(),
+            responder: interplay.responder,
+        };
+        let res = self.send(msg);
+        fetcher.grasp(res)
+    }
+
+    fn cancel_job(&self, id: JobId) -> Result<()> {
+        self.send(CancelJob { id })
+    }
+}
+
+struct Submit<M: Mission> {
+    job: Job<M>,
+    responder: Responder<M::Goal>,
+}
+
+#[async_trait]
+impl<M: Mission> MessageFor<JobQueue<M>> for Submit<M> {
+    async fn handle(
+        self: Box<Self>,
+        agent: &mut JobQueue<M>,
+        ctx: &mut Context<JobQueue<M>>,
+    ) -> Result<()> {
+        agent.enqueue(self.job, self.responder);
+        agent.dispatch(ctx);
+        Ok(())
+    }
+}
+
+struct CancelJob {
+    id: JobId,
+}
+
+#[async_trait]
+impl<M: Mission> MessageFor<JobQueue<M>> for CancelJob {
+    async fn handle(
+        self: Box<Self>,
+        agent: &mut JobQueue<M>,
+        _ctx: &mut Context<JobQueue<M>>,
+    ) -> Result<()> {
+        agent.cancel(self.id);
+        Ok(())
+    }
+}
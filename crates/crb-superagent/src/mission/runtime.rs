@@ -1,18 +1,35 @@
-use super::{Mission, Observer};
-use anyhow::{anyhow, Result};
+use super::{CancelHandle, Mission, Observer};
+use anyhow::{anyhow, Error};
 use async_trait::async_trait;
 use crb_agent::RunAgent;
 use crb_runtime::{
-    InteractiveRuntime, InteractiveTask, Interruptor, ReachableContext, Runtime, Task,
+    InteractiveRuntime, InteractiveTask, InterruptionLevel, Interruptor, ReachableContext, Runtime,
+    Task,
 };
 use futures::FutureExt;
-use std::any::type_name;
 use std::future::{Future, IntoFuture};
 use std::pin::Pin;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum MissionError {
+    #[error("Mission canceled with {level:?}: {reason}")]
+    Canceled {
+        reason: String,
+        level: InterruptionLevel,
+    },
+    #[error("Mission finished without a goal")]
+    NoGoal,
+    #[error("Mission timed out")]
+    TimedOut,
+    #[error("Mission crashed: {0}")]
+    Crashed(#[source] Error),
+}
 
 pub struct RunMission<M: Mission> {
     pub runtime: RunAgent<M>,
     pub observers: Vec<Box<dyn Observer<M>>>,
+    cancel: CancelHandle<M>,
 }
 
 impl<M: Mission> RunMission<M> {
@@ -20,34 +37,63 @@ impl<M: Mission> RunMission<M> {
     where
         M::Context: Default,
     {
+        let runtime = RunAgent::new(mission);
+        let cancel = CancelHandle::new(runtime.address());
         Self {
-            runtime: RunAgent::new(mission),
+            runtime,
             observers: Vec::new(),
+            cancel,
         }
     }
 
-    pub async fn operate(mut self) -> Result<M::Goal> {
-        self.perform()
-            .await
-            .ok_or_else(|| anyhow!("Mission {} failed", type_name::<M>()))
+    pub fn cancel_handle(&self) -> CancelHandle<M> {
+        self.cancel.clone()
+    }
+
+    pub async fn operate(mut self) -> Result<M::Goal, MissionError> {
+        self.perform().await
     }
 
-    pub async fn perform(&mut self) -> Option<M::Goal> {
-        self.runtime.perform().await;
-        if let Some(agent) = self.runtime.agent.take() {
-            let output = agent.deliver(&mut self.runtime.context).await;
-            if let Some(output) = output.as_ref() {
-                for observer in &mut self.observers {
-                    observer.check(output).ok();
-                }
+    pub async fn perform(&mut self) -> Result<M::Goal, MissionError> {
+        let name = std::any::type_name::<M>();
+        log::info!("Mission {name} started.");
+        let mut crash = None;
+        if let Err(err) = self.runtime.perform_abortable_task().await {
+            // The rollback gets the original error and the `MissionError` keeps its chain
+            crash = Some(anyhow!("{err:#}"));
+            let context = &mut self.runtime.context;
+            M::rollback(self.runtime.agent.as_mut(), err, context).await;
+        }
+        log::info!("Mission {name} finished.");
+        let canceled = self
+            .cancel
+            .cancellation()
+            .map(|(reason, level)| MissionError::Canceled { reason, level });
+        let output = match (self.runtime.agent.take(), canceled, crash) {
+            (Some(agent), None, _) => {
+                let no_goal = agent.no_goal();
+                agent
+                    .deliver(&mut self.runtime.context)
+                    .await
+                    .ok_or(no_goal)
+            }
+            (Some(agent), Some(canceled), _) => agent
+                .deliver_partial(&mut self.runtime.context)
+                .await
+                .ok_or(canceled),
+            (None, Some(canceled), _) => Err(canceled),
+            (None, None, crash) => Err(MissionError::Crashed(
+                crash.unwrap_or_else(|| anyhow!("The mission was consumed")),
+            )),
+        };
+        if let Ok(goal) = output.as_ref() {
+            for observer in &mut self.observers {
+                observer.check(goal).ok();
             }
-            let interrupted = output.is_none();
-            self.runtime.report(interrupted);
-            output
-        } else {
-            self.runtime.report(true);
-            None
         }
+        let interrupted = output.is_err();
+        self.runtime.report(interrupted);
+        output
     }
 }
 
@@ -60,11 +106,15 @@ where
     T: Mission,
 {
     fn get_interruptor(&mut self) -> Box<dyn Interruptor> {
-        self.runtime.get_interruptor()
+        Box::new(self.cancel.clone())
+    }
+
+    fn interruption_level(&self) -> InterruptionLevel {
+        self.runtime.interruption_level()
     }
 
     async fn routine(&mut self) {
-        self.perform().await;
+        self.perform().await.ok();
     }
 }
 
@@ -78,8 +128,8 @@ impl<M: Mission> InteractiveRuntime for RunMission<M> {
 }
 
 impl<M: Mission> IntoFuture for RunMission<M> {
-    type Output = Result<M::Goal>;
-    type IntoFuture = Pin<Box<dyn Future<Output = Result<M::Goal>> + Send>>;
+    type Output = Result<M::Goal, MissionError>;
+    type IntoFuture = Pin<Box<dyn Future<Output = Self::Output> + Send>>;
 
     fn into_future(self) -> Self::IntoFuture {
         self.operate().boxed()
@@ -1,27 +1,52 @@
+use anyhow::{Error, Result};
 use async_trait::async_trait;
-use crb_agent::performers::{ConsumptionReason, Next, StatePerformer, Transition};
-use crb_agent::{Address, Agent, AgentContext, AgentSession, Context, Envelope, RunAgent};
-use crb_runtime::{Controller, Interruptor, ManagedContext, ReachableContext, Runtime, Task};
+use crb_agent::performers::{
+    ConsumptionReason, Next, StatePerformer, StopReason, Transition, TransitionCommand,
+};
+use crb_agent::{
+    Address, Agent, AgentContext, AgentSession, Context, Envelope, Event, RunAgent, ToRecipient,
+};
+use crb_core::{mpsc, watch};
+use crb_runtime::{
+    Controller, InteractiveRuntime, InteractiveTask, InterruptionLevel, Interruptor,
+    ManagedContext, ReachableContext, Runtime, Stopper, Task,
+};
+use crb_send::{Recipient, Sender};
+use futures::future::{select, Either};
+use std::any::type_name;
+use std::collections::VecDeque;
 use std::marker::PhantomData;
+use std::sync::{Arc, Mutex};
 
-pub trait NextExt<A> {
+pub trait NextExt<A: Agent> {
     fn molt<T>() -> Self
     where
         A: MoltTo<T>,
-        T: Agent<Context = MoltingSession<T>>;
+        MoltPerformer<T>: StatePerformer<A>;
+
+    fn molt_async() -> Self
+    where
+        AsyncMoltPerformer: StatePerformer<A>;
 }
 
 impl<A> NextExt<A> for Next<A>
 where
-    A: Agent<Context = MoltingSession<A>>,
+    A: Agent,
 {
     fn molt<T>() -> Self
     where
         A: MoltTo<T>,
-        T: Agent<Context = MoltingSession<T>>,
+        MoltPerformer<T>: StatePerformer<A>,
     {
         Self::new(MoltPerformer::<T> { _type: PhantomData })
     }
+
+    fn molt_async() -> Self
+    where
+        AsyncMoltPerformer: StatePerformer<A>,
+    {
+        Self::new(AsyncMoltPerformer)
+    }
 }
 
 pub trait MoltTo<T>: Sized {
@@ -30,43 +55,184 @@ pub trait MoltTo<T>: Sized {
     }
 }
 
+/// An asynchronous molting with access to the context of the current form.
+///
+/// The next form is chosen at runtime and can be any form with the same protocol.
+#[async_trait]
+pub trait MoltAsync<P: Send + 'static = NoProtocol>: Form<P> {
+    async fn molt_async(self, ctx: &mut Context<Self>) -> Result<NextForm<P>, MoltError<Self>>;
+}
+
+/// A failed molting.
+///
+/// If the agent is returned back, the error is passed to `Agent::failed`,
+/// otherwise the agent is considered consumed and `Agent::rollback` is called.
+pub struct MoltError<A> {
+    pub agent: Option<A>,
+    pub error: Error,
+}
+
+impl<A> MoltError<A> {
+    /// Returns the agent back to handle the error with `Agent::failed`.
+    pub fn recover(agent: A, error: Error) -> Self {
+        Self {
+            agent: Some(agent),
+            error,
+        }
+    }
+}
+
+impl<A> From<Error> for MoltError<A> {
+    fn from(error: Error) -> Self {
+        Self { agent: None, error }
+    }
+}
+
+type Attach<P> = Box<
+    dyn FnOnce(Option<mpsc::UnboundedReceiver<P>>, VecDeque<P>) -> Box<dyn MoltingRuntime> + Send,
+>;
+
+/// The next form of a molting agent.
+pub struct NextForm<P = NoProtocol> {
+    attach: Attach<P>,
+}
+
+impl<P: Send + 'static> NextForm<P> {
+    pub fn new<T>(agent: T) -> Self
+    where
+        T: Form<P>,
+    {
+        let attach = move |inbox, forwarded| {
+            let mut runtime = RunAgent::new(agent);
+            // Pending messages of the protocol are delivered to the next form
+            runtime.context.inbox = inbox;
+            runtime.context.forwarded = forwarded;
+            Box::new(runtime) as Box<dyn MoltingRuntime>
+        };
+        Self {
+            attach: Box::new(attach),
+        }
+    }
+
+    /// Forwards messages of the protocol taken by the current form to the next one,
+    /// other pending messages of the current form are sent to dead letters.
+    fn attach_to<A>(self, session: &mut Context<A>)
+    where
+        A: Form<P>,
+    {
+        let mut forwarded = std::mem::take(&mut session.forwarded);
+        session
+            .session
+            .take_pending(|envelope| A::take_message(envelope).map(|msg| forwarded.push_back(msg)));
+        let inbox = session.inbox.take();
+        session.next_runtime = Some((self.attach)(inbox, forwarded));
+    }
+}
+
+/// A protocol of agents that don't share messages between forms.
+pub enum NoProtocol {}
+
+/// A form of a molting agent.
+///
+/// All forms of the same agent share the protocol `P` and
+/// receive its messages from the stable `MoltAddress<P>`.
+pub trait Form<P: Send + 'static = NoProtocol>: Agent<Context = MoltingSession<Self, P>> {
+    /// Wraps a message of the protocol into an envelope of the current form.
+    fn envelope(msg: P) -> Envelope<Self>;
+
+    /// Takes a message of the protocol back from an envelope of the current form.
+    ///
+    /// Pending messages of the protocol are forwarded to the next form on molting.
+    /// The default implementation takes messages wrapped by `Event::envelope`.
+    fn take_message(envelope: Envelope<Self>) -> Result<P, Envelope<Self>> {
+        Event::take(envelope)
+    }
+}
+
+impl<A> Form<NoProtocol> for A
+where
+    A: Agent<Context = MoltingSession<A, NoProtocol>>,
+{
+    fn envelope(msg: NoProtocol) -> Envelope<Self> {
+        match msg {}
+    }
+}
+
 pub struct MoltPerformer<T> {
     _type: PhantomData<T>,
 }
 
 #[async_trait]
-impl<A, T> StatePerformer<A> for MoltPerformer<T>
+impl<A, T, P> StatePerformer<A> for MoltPerformer<T>
 where
-    A: Agent<Context = MoltingSession<A>>,
+    A: Agent<Context = MoltingSession<A, P>> + Form<P>,
     A: MoltTo<T>,
-    T: Agent<Context = MoltingSession<T>>,
+    T: Agent<Context = MoltingSession<T, P>> + Form<P>,
+    P: Send + 'static,
 {
     async fn perform(&mut self, agent: A, session: &mut Context<A>) -> Transition<A> {
-        let next_agent = agent.molt();
-        if let Some(next_agent) = next_agent {
-            let next_runtime = RunAgent::new(next_agent);
-            session.next_runtime = Some(Box::new(next_runtime));
+        // The previous form doesn't receive messages anymore
+        session.shutdown();
+        match agent.molt() {
+            Some(next_agent) => NextForm::new(next_agent).attach_to(session),
+            None => session.session.reject_pending(),
         }
         let reason = ConsumptionReason::Transformed;
         Transition::Consume { reason }
     }
 }
 
-pub struct MoltingSession<A: Agent> {
+pub struct AsyncMoltPerformer;
+
+#[async_trait]
+impl<A, P> StatePerformer<A> for AsyncMoltPerformer
+where
+    A: Agent<Context = MoltingSession<A, P>> + MoltAsync<P>,
+    P: Send + 'static,
+{
+    async fn perform(&mut self, agent: A, session: &mut Context<A>) -> Transition<A> {
+        match agent.molt_async(session).await {
+            Ok(next_form) => {
+                session.shutdown();
+                next_form.attach_to(session);
+                let reason = ConsumptionReason::Transformed;
+                Transition::Consume { reason }
+            }
+            Err(MoltError {
+                agent: Some(agent),
+                error,
+            }) => {
+                let command = TransitionCommand::Stop(StopReason::Failed(error));
+                Transition::Continue { agent, command }
+            }
+            Err(MoltError { agent: None, error }) => {
+                let reason = ConsumptionReason::Crashed(error);
+                Transition::Consume { reason }
+            }
+        }
+    }
+}
+
+pub struct MoltingSession<A: Agent, P = NoProtocol> {
     pub session: AgentSession<A>,
     pub next_runtime: Option<Box<dyn MoltingRuntime>>,
+    pub inbox: Option<mpsc::UnboundedReceiver<P>>,
+    /// Messages of the protocol forwarded by the previous form.
+    pub forwarded: VecDeque<P>,
 }
 
-impl<A: Agent> Default for MoltingSession<A> {
+impl<A: Agent, P> Default for MoltingSession<A, P> {
     fn default() -> Self {
         Self {
             session: AgentSession::default(),
             next_runtime: None,
+            inbox: None,
+            forwarded: VecDeque::new(),
         }
     }
 }
 
-impl<A: Agent> ReachableContext for MoltingSession<A> {
+impl<A: Agent, P: Send> ReachableContext for MoltingSession<A, P> {
     type Address = Address<A>;
 
     fn address(&self) -> &Self::Address {
@@ -74,9 +240,10 @@ impl<A: Agent> ReachableContext for MoltingSession<A> {
     }
 }
 
-impl<A> ManagedContext for MoltingSession<A>
+impl<A, P> ManagedContext for MoltingSession<A, P>
 where
     A: Agent,
+    P: Send,
 {
     fn is_alive(&self) -> bool {
         self.session.is_alive()
@@ -92,59 +259,256 @@ where
 }
 
 #[async_trait]
-impl<A: Agent> AgentContext<A> for MoltingSession<A> {
+impl<A, P> AgentContext<A> for MoltingSession<A, P>
+where
+    A: Form<P>,
+    P: Send + 'static,
+{
     fn session(&mut self) -> &mut AgentSession<A> {
         &mut self.session
     }
 
     async fn next_envelope(&mut self) -> Option<Envelope<A>> {
-        self.session.next_envelope().await
+        if let Some(msg) = self.forwarded.pop_front() {
+            return Some(A::envelope(msg));
+        }
+        let next_fut = self.session.next_envelope();
+        let Some(inbox) = self.inbox.as_mut() else {
+            return next_fut.await;
+        };
+        let msg_fut = Box::pin(inbox.recv());
+        let next_fut = match select(next_fut, msg_fut).await {
+            Either::Left((envelope, _)) => return envelope,
+            Either::Right((Some(msg), _)) => return Some(A::envelope(msg)),
+            Either::Right((None, next_fut)) => next_fut,
+        };
+        // All stable addresses are dropped
+        self.inbox.take();
+        next_fut.await
+    }
+}
+
+/// A stable address of a molting agent that doesn't depend on the current form.
+pub struct MoltAddress<P> {
+    msg_tx: mpsc::UnboundedSender<P>,
+    form_rx: watch::Receiver<Option<&'static str>>,
+    interruptor: MoltInterruptor,
+}
+
+impl<P> Clone for MoltAddress<P> {
+    fn clone(&self) -> Self {
+        Self {
+            msg_tx: self.msg_tx.clone(),
+            form_rx: self.form_rx.clone(),
+            interruptor: self.interruptor.clone(),
+        }
+    }
+}
+
+impl<P: Send + 'static> MoltAddress<P> {
+    pub fn send(&self, msg: P) -> Result<()> {
+        self.msg_tx
+            .send(msg)
+            .map_err(|_| Error::msg("Can't send the message to the molting agent"))
+    }
+
+    /// A type name of the current form or `None` if the agent has finished.
+    pub fn form(&self) -> Option<&'static str> {
+        *self.form_rx.borrow()
+    }
+
+    pub fn interrupt(&self) {
+        self.interruptor.interrupt();
+    }
+
+    pub async fn join(&mut self) -> Result<()> {
+        self.form_rx.wait_for(Option::is_none).await?;
+        Ok(())
+    }
+}
+
+impl<P: Send + 'static> Sender<P> for MoltAddress<P> {
+    fn send(&self, msg: P) -> Result<()> {
+        MoltAddress::send(self, msg)
+    }
+}
+
+impl<P: Send + 'static> Interruptor for MoltAddress<P> {
+    fn interrupt(&self) {
+        self.interruptor.interrupt();
     }
+
+    fn interrupt_with_level(&self, level: InterruptionLevel) {
+        self.interruptor.interrupt_with_level(level);
+    }
+}
+
+/// Forwards interruptions to the current form of a molting agent.
+#[derive(Clone)]
+pub struct MoltInterruptor {
+    stopper: Stopper,
+    current: Arc<Mutex<Option<Box<dyn Interruptor>>>>,
 }
 
-pub struct MoltAgent {
+impl MoltInterruptor {
+    fn new(stopper: Stopper) -> Self {
+        Self {
+            stopper,
+            current: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    fn set(&self, interruptor: Option<Box<dyn Interruptor>>) {
+        if let Ok(mut current) = self.current.lock() {
+            *current = interruptor;
+        }
+    }
+}
+
+impl Interruptor for MoltInterruptor {
+    fn interrupt(&self) {
+        self.interrupt_with_level(InterruptionLevel::FLAG);
+    }
+
+    fn interrupt_with_level(&self, level: InterruptionLevel) {
+        // Prevents the next molting
+        self.stopper.stop(false);
+        if let Ok(current) = self.current.lock() {
+            if let Some(interruptor) = current.as_ref() {
+                interruptor.interrupt_with_level(level);
+            }
+        }
+    }
+}
+
+/// An event that is sent to observers every time a `MoltAgent` changes its form.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Molted {
+    pub from: &'static str,
+    pub to: &'static str,
+}
+
+pub struct MoltContext<P> {
+    address: MoltAddress<P>,
+}
+
+impl<P: Send + 'static> ReachableContext for MoltContext<P> {
+    type Address = MoltAddress<P>;
+
+    fn address(&self) -> &Self::Address {
+        &self.address
+    }
+}
+
+pub struct MoltAgent<P = NoProtocol> {
     current_runtime: Option<Box<dyn MoltingRuntime>>,
     controller: Controller,
+    interruptor: MoltInterruptor,
+    form_tx: watch::Sender<Option<&'static str>>,
+    observers: Vec<Recipient<Molted>>,
+    context: MoltContext<P>,
 }
 
-impl MoltAgent {
+impl<P: Send + 'static> MoltAgent<P> {
     pub fn new<A>(agent: A) -> Self
     where
-        A: Agent<Context = MoltingSession<A>>,
+        A: Form<P>,
     {
-        let runtime = RunAgent::new(agent);
+        let (msg_tx, msg_rx) = mpsc::unbounded_channel();
+        let mut runtime = RunAgent::new(agent);
+        runtime.context.inbox = Some(msg_rx);
+        let controller = Controller::default();
+        let interruptor = MoltInterruptor::new(controller.stopper.clone());
+        let (form_tx, form_rx) = watch::channel(Some(type_name::<A>()));
+        let address = MoltAddress {
+            msg_tx,
+            form_rx,
+            interruptor: interruptor.clone(),
+        };
         Self {
             current_runtime: Some(Box::new(runtime)),
-            controller: Controller::default(),
+            controller,
+            interruptor,
+            form_tx,
+            observers: Vec::new(),
+            context: MoltContext { address },
         }
     }
+
+    /// An address that remains valid across all forms of the agent.
+    pub fn molt_address(&self) -> MoltAddress<P> {
+        self.context.address.clone()
+    }
+
+    /// Sends a `Molted` event to the recipient on every transition.
+    pub fn report_to(&mut self, recipient: impl ToRecipient<Molted>) {
+        self.observers.push(recipient.to_recipient());
+    }
 }
 
-impl Task for MoltAgent {}
+impl<P: Send + 'static> Task for MoltAgent<P> {}
+impl<P: Send + 'static> InteractiveTask<()> for MoltAgent<P> {}
 
 #[async_trait]
-impl Runtime for MoltAgent {
+impl<P: Send + 'static> Runtime for MoltAgent<P> {
     fn get_interruptor(&mut self) -> Box<dyn Interruptor> {
-        Box::new(self.controller.stopper.clone())
+        Box::new(self.interruptor.clone())
+    }
+
+    fn interruption_level(&self) -> InterruptionLevel {
+        InterruptionLevel::FLAG
     }
 
     async fn routine(&mut self) {
+        let mut previous = None;
         while let Some(mut runtime) = self.current_runtime.take() {
+            let form = runtime.form();
+            self.form_tx.send_replace(Some(form));
+            if let Some(from) = previous {
+                let event = Molted { from, to: form };
+                log::trace!("Agent molted from {from} to {form}");
+                for observer in &self.observers {
+                    observer.send(event).ok();
+                }
+            }
+            // The interruptor has to be set before checking the flag
+            // to never miss an interruption between forms.
+            self.interruptor.set(Some(runtime.get_interruptor()));
+            if !self.controller.stopper.is_active() {
+                break;
+            }
             runtime.routine().await;
-            let next_runtime = runtime.do_molting();
-            self.current_runtime = next_runtime;
+            previous = Some(form);
+            self.current_runtime = runtime.do_molting();
         }
+        self.interruptor.set(None);
+        self.form_tx.send_replace(None);
+    }
+}
+
+impl<P: Send + 'static> InteractiveRuntime for MoltAgent<P> {
+    type Context = MoltContext<P>;
+
+    fn address(&self) -> <Self::Context as ReachableContext>::Address {
+        self.molt_address()
     }
 }
 
 pub trait MoltingRuntime: Runtime {
+    fn form(&self) -> &'static str;
+
     fn do_molting(self: Box<Self>) -> Option<Box<dyn MoltingRuntime>>;
 }
 
-impl<A> MoltingRuntime for RunAgent<A>
+impl<A, P> MoltingRuntime for RunAgent<A>
 where
-    A: Agent<Context = MoltingSession<A>>,
+    A: Agent<Context = MoltingSession<A, P>> + Form<P>,
+    P: Send + 'static,
 {
+    fn form(&self) -> &'static str {
+        type_name::<A>()
+    }
+
     fn do_molting(mut self: Box<Self>) -> Option<Box<dyn MoltingRuntime>> {
         self.context.next_runtime.take()
     }
@@ -0,0 +1,75 @@
+use super::{Relation, Supervisor};
+use anyhow::Result;
+use crb_core::watch;
+use crb_runtime::{InterruptionLevel, Interruptor};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AssignmentStatus {
+    Active,
+    /// Cancellation has been requested, but the task is not finished yet.
+    Canceling,
+    Finished,
+    /// The task has been finished after cancellation.
+    Canceled,
+}
+
+/// A handle of a task assigned to a supervisor.
+pub struct Assignment<S: Supervisor> {
+    rel: Relation<S>,
+    interruptor: Box<dyn Interruptor>,
+    finished: watch::Receiver<bool>,
+    canceled: bool,
+}
+
+impl<S: Supervisor> Assignment<S> {
+    pub(super) fn new(
+        rel: Relation<S>,
+        interruptor: Box<dyn Interruptor>,
+        finished: watch::Receiver<bool>,
+    ) -> Self {
+        Self {
+            rel,
+            interruptor,
+            finished,
+            canceled: false,
+        }
+    }
+
+    pub fn relation(&self) -> &Relation<S> {
+        &self.rel
+    }
+
+    pub fn status(&self) -> AssignmentStatus {
+        let finished = *self.finished.borrow();
+        match (finished, self.canceled) {
+            (false, false) => AssignmentStatus::Active,
+            (false, true) => AssignmentStatus::Canceling,
+            (true, false) => AssignmentStatus::Finished,
+            (true, true) => AssignmentStatus::Canceled,
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        *self.finished.borrow()
+    }
+
+    /// Interrupts the task with the level.
+    pub fn cancel(&mut self, level: InterruptionLevel) {
+        if !self.is_finished() {
+            self.canceled = true;
+            self.interruptor.interrupt_with_level(level);
+        }
+    }
+
+    /// Waits for the end of the task.
+    pub async fn join(&mut self) -> Result<AssignmentStatus> {
+        self.finished.wait_for(|finished| *finished).await?;
+        Ok(self.status())
+    }
+}
+
+/// An event about the end of an assigned task.
+#[derive(Debug, Clone)]
+pub struct AssignmentFinished<T> {
+    pub tag: T,
+}
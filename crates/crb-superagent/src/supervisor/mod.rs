@@ -1,21 +1,29 @@
+pub mod assignment;
 pub mod forward;
+pub mod policy;
+pub mod pool;
 pub mod stacker;
 
+pub use assignment::{Assignment, AssignmentFinished, AssignmentStatus};
 pub use forward::ForwardTo;
+pub use policy::GroupPolicy;
+pub use pool::{Pool, PoolExt, Routed, Routing};
 pub use stacker::Stacker;
 
-use anyhow::Error;
+use anyhow::{anyhow, Error, Result};
 use async_trait::async_trait;
 use crb_agent::{
-    Address, Agent, AgentContext, AgentSession, Context, Envelope, MessageFor, RunAgent,
+    Address, Agent, AgentContext, AgentSession, Context, Envelope, MessageFor, OnEvent, RunAgent,
 };
-use crb_core::Tag;
+use crb_core::time::{sleep, Duration};
+use crb_core::{watch, Tag};
 use crb_runtime::{
     InteractiveRuntime, InterruptionLevel, Interruptor, ManagedContext, ReachableContext, Runtime,
 };
 use derive_more::{Deref, DerefMut, From, Into};
+use policy::GroupTimeout;
 use std::cmp::Ordering;
-use std::collections::{BTreeMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fmt::Debug;
 use std::hash::{Hash, Hasher};
 use typed_slab::TypedSlab;
@@ -28,6 +36,15 @@ pub trait Supervisor: Agent {
     type GroupBy: Debug + Ord + Clone + Send + Eq + Hash;
 
     fn finished(&mut self, _rel: &Relation<Self>, _ctx: &mut Context<Self>) {}
+
+    /// Called for an activity that ignored all interruption levels,
+    /// or the interruption of a group without escalation.
+    ///
+    /// The activity is detached and no longer blocks the termination,
+    /// but `finished` will still be called if it ever stops.
+    /// An abandoned activity keeps running after the supervisor has finished,
+    /// nothing stops it anymore.
+    fn stuck(&mut self, _rel: &Relation<Self>, _ctx: &mut Context<Self>) {}
 }
 
 pub trait SupervisorContext<S: Supervisor> {
@@ -111,6 +128,8 @@ impl<S: Supervisor> SupervisorContext<S> for SupervisorSession<S> {
 #[derive(Debug, Default)]
 struct Group {
     interrupted: bool,
+    /// Changes with every interruption to skip outdated timeouts.
+    generation: u64,
     ids: HashSet<ActivityId>,
 }
 
@@ -120,10 +139,23 @@ impl Group {
     }
 }
 
+type Schedule<S> = Box<dyn Fn(<S as Supervisor>::GroupBy, u64, Duration) + Send>;
+
 pub struct Tracker<S: Supervisor> {
     groups: BTreeMap<S::GroupBy, Group>,
     activities: TypedSlab<ActivityId, Activity<S>>,
     terminating: bool,
+    policies: HashMap<S::GroupBy, GroupPolicy>,
+    /// Groups and groups they depend on.
+    dependencies: HashMap<S::GroupBy, HashSet<S::GroupBy>>,
+    /// Terminated groups that can't be restarted.
+    closed: HashSet<S::GroupBy>,
+    schedule: Option<Schedule<S>>,
+    generation: u64,
+    /// The timeout for groups that have no own timeout in their policies.
+    grace_period: Option<Duration>,
+    /// Stuck activities that are still running.
+    abandoned: usize,
 }
 
 impl<S: Supervisor> Default for Tracker<S> {
@@ -138,25 +170,78 @@ impl<S: Supervisor> Tracker<S> {
             groups: BTreeMap::new(),
             activities: TypedSlab::new(),
             terminating: false,
+            policies: HashMap::new(),
+            dependencies: HashMap::new(),
+            closed: HashSet::new(),
+            schedule: None,
+            generation: 0,
+            grace_period: None,
+            abandoned: 0,
         }
     }
 
     pub fn is_empty(&self) -> bool {
-        self.groups.is_empty() && self.activities.is_empty()
+        self.groups.is_empty() && self.activities.len() == self.abandoned
     }
 
     pub fn is_terminated(&self) -> bool {
         self.terminating && self.is_empty()
     }
 
-    pub fn terminate_group(&mut self, group: S::GroupBy) {
-        if let Some(group) = self.groups.get(&group) {
-            for id in group.ids.iter() {
-                if let Some(activity) = self.activities.get_mut(*id) {
-                    activity.interrupt();
+    pub fn is_terminating(&self) -> bool {
+        self.terminating
+    }
+
+    pub fn set_policy(&mut self, group: S::GroupBy, policy: GroupPolicy) {
+        self.policies.insert(group, policy);
+    }
+
+    /// Sets the time given to activities to react to an interruption
+    /// before they are interrupted with the next level.
+    pub fn set_grace_period(&mut self, grace_period: Duration) {
+        self.grace_period = Some(grace_period);
+    }
+
+    /// The `group` uses `depends_on`, so it is terminated first and
+    /// `depends_on` is interrupted only when the `group` has finished.
+    ///
+    /// Groups with dependencies are ordered by them only,
+    /// other groups are terminated in the reverse order of `GroupBy`.
+    ///
+    /// A dependency that makes a cycle is rejected, since groups of the cycle
+    /// would wait for each other forever.
+    pub fn add_dependency(&mut self, group: S::GroupBy, depends_on: S::GroupBy) -> Result<()> {
+        if self.depends_on(&depends_on, &group) {
+            return Err(anyhow!(
+                "The dependency of {group:?} on {depends_on:?} makes a cycle"
+            ));
+        }
+        self.dependencies
+            .entry(group)
+            .or_default()
+            .insert(depends_on);
+        Ok(())
+    }
+
+    /// Checks if the `group` depends on the `other` directly or transitively.
+    fn depends_on(&self, group: &S::GroupBy, other: &S::GroupBy) -> bool {
+        let mut visited = HashSet::new();
+        let mut pending = vec![group];
+        while let Some(next) = pending.pop() {
+            if next == other {
+                return true;
+            }
+            if visited.insert(next) {
+                if let Some(deps) = self.dependencies.get(next) {
+                    pending.extend(deps);
                 }
             }
         }
+        false
+    }
+
+    pub fn terminate_group(&mut self, group: S::GroupBy) {
+        self.interrupt_group(&group);
     }
 
     pub fn terminate_all(&mut self) {
@@ -170,7 +255,13 @@ impl<S: Supervisor> Tracker<S> {
         group_record.ids.insert(id);
         if group_record.interrupted {
             // Interrupt if the group is terminating
-            self.activities.get_mut(id).map(Activity::interrupt);
+            let level = self.policy(&group).level;
+            if let Some(activity) = self.activities.get_mut(id) {
+                activity.interrupt(level);
+            }
+        } else if self.closed.contains(&group) {
+            // A reopened group is terminated again with its timeout
+            self.interrupt_group(&group);
         }
         Relation { id, group }
     }
@@ -178,18 +269,148 @@ impl<S: Supervisor> Tracker<S> {
     fn unregister_activity(&mut self, rel: &Relation<S>) {
         if let Some(activity) = self.activities.remove(rel.id) {
             // TODO: check rel.group == activity.group ?
-            if let Some(group) = self.groups.get_mut(&activity.group) {
-                group.ids.remove(&rel.id);
-                if group.ids.is_empty() {
-                    self.groups.remove(&activity.group);
+            if activity.abandoned {
+                self.abandoned -= 1;
+            } else {
+                self.leave_group(rel.id, &activity.group);
+            }
+        }
+        if self.terminating {
+            self.try_terminate_next();
+        }
+    }
+
+    fn leave_group(&mut self, id: ActivityId, group_name: &S::GroupBy) {
+        if let Some(group) = self.groups.get_mut(group_name) {
+            group.ids.remove(&id);
+            if group.ids.is_empty() {
+                let interrupted = group.interrupted;
+                self.groups.remove(group_name);
+                if interrupted && !self.policy(group_name).restartable {
+                    self.closed.insert(group_name.clone());
                 }
             }
         }
+    }
+
+    /// Detaches an activity that doesn't react to interruptions.
+    ///
+    /// The record is kept until the activity finishes to not reuse its id.
+    fn abandon_activity(&mut self, rel: &Relation<S>) {
+        if let Some(activity) = self.activities.get_mut(rel.id) {
+            if !activity.abandoned {
+                activity.abandoned = true;
+                self.abandoned += 1;
+                self.leave_group(rel.id, &rel.group);
+            }
+        }
         if self.terminating {
             self.try_terminate_next();
         }
     }
 
+    fn policy(&self, group: &S::GroupBy) -> GroupPolicy {
+        self.policies.get(group).cloned().unwrap_or_default()
+    }
+
+    fn timeout(&self, policy: &GroupPolicy) -> Option<Duration> {
+        policy.timeout.or(self.grace_period)
+    }
+
+    fn has_relations(&self, group: &S::GroupBy) -> bool {
+        self.dependencies.contains_key(group)
+            || self.dependencies.values().any(|deps| deps.contains(group))
+    }
+
+    /// Checks if all groups that must be terminated before the group are finished.
+    fn may_terminate(&self, group: &S::GroupBy) -> bool {
+        let is_finished = |other: &S::GroupBy| {
+            self.groups
+                .get(other)
+                .map(Group::is_finished)
+                .unwrap_or(true)
+        };
+        if self.has_relations(group) {
+            self.dependencies
+                .iter()
+                .filter(|(_, deps)| deps.contains(group))
+                .all(|(dependent, _)| is_finished(dependent))
+        } else {
+            self.groups
+                .keys()
+                .filter(|other| *other > group)
+                .all(is_finished)
+        }
+    }
+
+    fn interrupt_group(&mut self, group_name: &S::GroupBy) {
+        let policy = self.policy(group_name);
+        let Some(group) = self.groups.get_mut(group_name) else {
+            return;
+        };
+        let name = std::any::type_name::<S>();
+        log::trace!("Agent {name} is terminating group: {:?}", group_name);
+        self.generation += 1;
+        group.interrupted = true;
+        group.generation = self.generation;
+        // Send an interruption signal to all active members of the group.
+        for id in group.ids.iter() {
+            if let Some(activity) = self.activities.get_mut(*id) {
+                activity.interrupt(policy.level);
+            }
+        }
+        if let (Some(timeout), Some(schedule)) = (self.timeout(&policy), &self.schedule) {
+            schedule(group_name.clone(), self.generation, timeout);
+        }
+    }
+
+    /// Escalates interruptions of the group and returns activities
+    /// that are stuck at the highest level.
+    ///
+    /// All remaining activities are stuck if the group has no escalation.
+    fn group_timeout(&mut self, group_name: S::GroupBy, generation: u64) -> Vec<Relation<S>> {
+        let policy = self.policy(&group_name);
+        let timeout = self.timeout(&policy);
+        let Some(group) = self.groups.get_mut(&group_name) else {
+            return Vec::new();
+        };
+        if group.generation != generation {
+            return Vec::new();
+        }
+        let name = std::any::type_name::<S>();
+        if policy.escalate {
+            log::warn!(
+                "Agent {name} escalates termination of group: {:?}",
+                group_name
+            );
+        }
+        self.generation += 1;
+        group.generation = self.generation;
+        let mut escalated = false;
+        let mut stuck = Vec::new();
+        for id in group.ids.iter() {
+            if let Some(activity) = self.activities.get_mut(*id) {
+                if policy.escalate && activity.escalate() {
+                    escalated = true;
+                } else {
+                    let rel = Relation {
+                        id: *id,
+                        group: group_name.clone(),
+                    };
+                    stuck.push(rel);
+                }
+            }
+        }
+        for rel in &stuck {
+            log::error!("Agent {name} abandons stuck activity: {:?}", rel.id);
+            self.abandon_activity(rel);
+        }
+        if let (true, Some(timeout), Some(schedule)) = (escalated, timeout, &self.schedule) {
+            schedule(group_name, self.generation, timeout);
+        }
+        stuck
+    }
+
     fn existing_groups(&self) -> Vec<S::GroupBy> {
         self.groups.keys().rev().cloned().collect()
     }
@@ -197,21 +418,12 @@ impl<S: Supervisor> Tracker<S> {
     fn try_terminate_next(&mut self) {
         self.terminating = true;
         for group_name in self.existing_groups() {
-            if let Some(group) = self.groups.get_mut(&group_name) {
-                let name = std::any::type_name::<S>();
-                log::trace!("Agent {name} is terminating group: {:?}", group_name);
-                if !group.interrupted {
-                    group.interrupted = true;
-                    // Send an interruption signal to all active members of the group.
-                    for id in group.ids.iter() {
-                        if let Some(activity) = self.activities.get_mut(*id) {
-                            activity.interrupt();
-                        }
-                    }
-                }
-                if !group.is_finished() {
-                    break;
-                }
+            let interrupted = self
+                .groups
+                .get(&group_name)
+                .is_some_and(|group| group.interrupted);
+            if !interrupted && self.may_terminate(&group_name) {
+                self.interrupt_group(&group_name);
             }
         }
     }
@@ -248,25 +460,55 @@ where
         (addr, rel)
     }
 
-    pub fn spawn_trackable<B>(&mut self, mut trackable: B, group: S::GroupBy) -> Relation<S>
+    pub fn spawn_trackable<B>(&mut self, trackable: B, group: S::GroupBy) -> Relation<S>
+    where
+        B: Runtime,
+    {
+        let (rel, _finished) = self.track(trackable, group, None);
+        rel
+    }
+
+    fn track<B>(
+        &mut self,
+        mut trackable: B,
+        group: S::GroupBy,
+        on_finish: Option<Box<dyn FnOnce() + Send>>,
+    ) -> (Relation<S>, watch::Receiver<bool>)
     where
         B: Runtime,
     {
+        if self.tracker.schedule.is_none() {
+            let address = self.address().clone();
+            let schedule: Schedule<S> = Box::new(move |group, generation, timeout| {
+                let address = address.clone();
+                crb_core::spawn(async move {
+                    sleep(timeout).await;
+                    address.send(GroupTimeout { group, generation }).ok();
+                });
+            });
+            self.tracker.schedule = Some(schedule);
+        }
         let activity = Activity {
             group,
             interruptor: trackable.get_interruptor(),
             level: trackable.interruption_level(),
+            abandoned: false,
         };
         let rel = self.tracker.register_activity(activity);
         let detacher = DetacherFor {
             supervisor: self.address().clone(),
             rel: rel.clone(),
         };
+        let (finished_tx, finished_rx) = watch::channel(false);
 
         let fut = async move {
             let name = std::any::type_name::<S>();
             let rn_name = std::any::type_name::<B>();
             trackable.routine().await;
+            finished_tx.send(true).ok();
+            if let Some(on_finish) = on_finish {
+                on_finish();
+            }
             // This notification equals calling `detach_trackable`
             if let Err(err) = detacher.detach() {
                 log::error!(
@@ -277,17 +519,55 @@ where
             }
         };
         crb_core::spawn(fut);
-        rel
+        (rel, finished_rx)
+    }
+
+    /// Spawns a task that forwards its result to the supervisor.
+    pub fn assign<R, T>(&mut self, trackable: R, group: S::GroupBy, tag: T) -> Assignment<S>
+    where
+        R: ForwardTo<S, T>,
+        T: Tag,
+    {
+        self.assign_inner(trackable, group, tag, None)
+    }
+
+    /// The same as `assign`, but the supervisor also gets
+    /// the `AssignmentFinished` event with a copy of the tag.
+    pub fn assign_notified<R, T>(
+        &mut self,
+        trackable: R,
+        group: S::GroupBy,
+        tag: T,
+    ) -> Assignment<S>
+    where
+        R: ForwardTo<S, T>,
+        T: Tag + Clone,
+        S: OnEvent<AssignmentFinished<T>>,
+    {
+        let address = self.address().clone();
+        let finished = AssignmentFinished { tag: tag.clone() };
+        let on_finish = Box::new(move || {
+            address.event(finished).ok();
+        });
+        self.assign_inner(trackable, group, tag, Some(on_finish))
     }
 
-    pub fn assign<R, T>(&mut self, trackable: R, group: S::GroupBy, tag: T) -> Relation<S>
+    fn assign_inner<R, T>(
+        &mut self,
+        trackable: R,
+        group: S::GroupBy,
+        tag: T,
+        on_finish: Option<Box<dyn FnOnce() + Send>>,
+    ) -> Assignment<S>
     where
         R: ForwardTo<S, T>,
         T: Tag,
     {
         let address = self.address().clone();
-        let trackable = trackable.into_trackable(address, tag);
-        self.spawn_trackable(trackable, group)
+        let mut trackable = trackable.into_trackable(address, tag);
+        let interruptor = trackable.get_interruptor();
+        let (rel, finished) = self.track(trackable, group, on_finish);
+        Assignment::new(rel, interruptor, finished)
     }
 }
 
@@ -295,12 +575,24 @@ struct Activity<S: Supervisor> {
     group: S::GroupBy,
     interruptor: Box<dyn Interruptor>,
     level: InterruptionLevel,
+    abandoned: bool,
 }
 
 impl<S: Supervisor> Activity<S> {
-    fn interrupt(&mut self) {
+    fn interrupt(&mut self, level: Option<InterruptionLevel>) {
+        if let Some(level) = level {
+            self.level = level;
+        }
         self.interruptor.interrupt_with_level(self.level);
     }
+
+    /// Interrupts with the next level. Returns `false` if the level is the highest.
+    fn escalate(&mut self) -> bool {
+        let next = self.level.next();
+        let escalated = next != self.level;
+        self.interrupt(Some(next));
+        escalated
+    }
 }
 
 pub struct Relation<S: Supervisor> {
@@ -0,0 +1,81 @@
+use super::{Supervisor, SupervisorContext};
+use anyhow::Result;
+use async_trait::async_trait;
+use crb_agent::{Context, MessageFor};
+use crb_core::time::Duration;
+use crb_runtime::{InterruptionLevel, ManagedContext};
+
+/// How a group of activities is terminated.
+#[derive(Debug, Clone)]
+pub struct GroupPolicy {
+    /// Time given to activities to stop after an interruption.
+    /// The grace period of the tracker is used if it's not set.
+    pub timeout: Option<Duration>,
+    /// The level used instead of levels of activities.
+    pub level: Option<InterruptionLevel>,
+    /// Interrupt activities with the next level when the timeout expires.
+    pub escalate: bool,
+    /// Allow new activities in the group after it has been terminated.
+    pub restartable: bool,
+}
+
+impl Default for GroupPolicy {
+    fn default() -> Self {
+        Self {
+            timeout: None,
+            level: None,
+            escalate: true,
+            restartable: true,
+        }
+    }
+}
+
+impl GroupPolicy {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    pub fn with_level(mut self, level: InterruptionLevel) -> Self {
+        self.level = Some(level);
+        self
+    }
+
+    pub fn without_escalation(mut self) -> Self {
+        self.escalate = false;
+        self
+    }
+
+    pub fn not_restartable(mut self) -> Self {
+        self.restartable = false;
+        self
+    }
+}
+
+pub(super) struct GroupTimeout<S: Supervisor> {
+    pub group: S::GroupBy,
+    pub generation: u64,
+}
+
+#[async_trait]
+impl<S> MessageFor<S> for GroupTimeout<S>
+where
+    S: Supervisor,
+    S::Context: SupervisorContext<S>,
+{
+    async fn handle(self: Box<Self>, agent: &mut S, ctx: &mut Context<S>) -> Result<()> {
+        let tracker = ctx.tracker();
+        let stuck = tracker.group_timeout(self.group, self.generation);
+        if tracker.is_terminated() {
+            ctx.shutdown();
+        }
+        for rel in stuck {
+            agent.stuck(&rel, ctx);
+        }
+        Ok(())
+    }
+}
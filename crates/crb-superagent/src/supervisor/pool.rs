@@ -0,0 +1,450 @@
+use super::{ActivityId, Relation, Supervisor, SupervisorSession};
+use crate::interplay::{Fetcher, Interaction, Interplay, OnRequest, Request};
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use crb_agent::{
+    Address, Agent, AgentSession, Backoff, Context, Event, MessageFor, Next, OnEvent, Standalone,
+    TheEvent,
+};
+use crb_core::time::{sleep, Duration};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, HashMap};
+use std::hash::{Hash, Hasher};
+
+/// Virtual nodes of every worker in the hash ring.
+const REPLICAS: usize = 16;
+
+/// The first delay before a replacement of a finished worker.
+const RESTART_DELAY: Duration = Duration::from_millis(10);
+
+/// A strategy to choose a worker for messages without a key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Routing {
+    #[default]
+    RoundRobin,
+    LeastLoaded,
+}
+
+struct Worker<W: Agent> {
+    address: Address<W>,
+    id: ActivityId,
+    load: usize,
+}
+
+/// An event that the pool routes to one of its workers.
+pub struct Routed<E>(pub E);
+
+/// A pool of identical workers.
+///
+/// Events wrapped into `Routed` and requests are routed to workers according
+/// to the `Routing` strategy, messages sent with a key are routed by consistent hashing.
+/// Finished workers are replaced with delays of the restart `Backoff`.
+pub struct Pool<W: Agent> {
+    factory: Box<dyn Fn() -> W + Send>,
+    size: usize,
+    routing: Routing,
+    restarts: Backoff,
+    workers: Vec<Option<Worker<W>>>,
+    slots: HashMap<ActivityId, usize>,
+    ring: BTreeMap<u64, usize>,
+    cursor: usize,
+}
+
+impl<W: Agent> Pool<W> {
+    pub fn new<F>(size: usize, factory: F) -> Self
+    where
+        F: Fn() -> W + Send + 'static,
+    {
+        Self {
+            factory: Box::new(factory),
+            size,
+            routing: Routing::default(),
+            restarts: Backoff::exponential(RESTART_DELAY),
+            workers: Vec::new(),
+            slots: HashMap::new(),
+            ring: BTreeMap::new(),
+            cursor: 0,
+        }
+    }
+
+    pub fn with_routing(mut self, routing: Routing) -> Self {
+        self.routing = routing;
+        self
+    }
+
+    /// Delays replacements of finished workers.
+    ///
+    /// Workers are not replaced anymore when the backoff gives up,
+    /// attempts are reset every time a worker handles a message.
+    pub fn with_restarts(mut self, restarts: Backoff) -> Self {
+        self.restarts = restarts;
+        self
+    }
+
+    pub fn size(&self) -> usize {
+        self.size
+    }
+}
+
+impl<W> Pool<W>
+where
+    W: Agent,
+    W::Context: Default,
+{
+    fn spawn_worker(&mut self, slot: usize, ctx: &mut Context<Self>) {
+        let agent = (self.factory)();
+        let (address, rel) = ctx.spawn_agent(agent, ());
+        self.slots.insert(rel.id, slot);
+        self.workers[slot] = Some(Worker {
+            address,
+            id: rel.id,
+            load: 0,
+        });
+    }
+
+    fn resize(&mut self, size: usize, ctx: &mut Context<Self>) {
+        self.size = size;
+        let current = self.workers.len();
+        if size > current {
+            self.workers.resize_with(size, || None);
+            for slot in current..size {
+                self.spawn_worker(slot, ctx);
+            }
+        } else {
+            for worker in self.workers.drain(size..).flatten() {
+                worker.address.interrupt().ok();
+            }
+        }
+        self.ring.clear();
+        for slot in 0..size {
+            for replica in 0..REPLICAS {
+                self.ring.insert(hash_of((slot, replica)), slot);
+            }
+        }
+    }
+
+    fn pick(&mut self, key: Option<u64>) -> Option<usize> {
+        if let Some(key) = key {
+            return self
+                .ring
+                .range(key..)
+                .next()
+                .or_else(|| self.ring.iter().next())
+                .map(|(_, slot)| *slot);
+        }
+        match self.routing {
+            Routing::RoundRobin => {
+                let len = self.workers.len();
+                let slot = (0..len)
+                    .map(|shift| (self.cursor + shift) % len)
+                    .find(|slot| self.workers[*slot].is_some())?;
+                self.cursor = (slot + 1) % len;
+                Some(slot)
+            }
+            Routing::LeastLoaded => self
+                .workers
+                .iter()
+                .enumerate()
+                .filter_map(|(slot, worker)| worker.as_ref().map(|worker| (slot, worker.load)))
+                .min_by_key(|(_, load)| *load)
+                .map(|(slot, _)| slot),
+        }
+    }
+
+    fn route<M>(&mut self, message: M, key: Option<u64>, ctx: &mut Context<Self>) -> Result<()>
+    where
+        M: MessageFor<W>,
+    {
+        let worker = self
+            .pick(key)
+            .and_then(|slot| self.workers.get_mut(slot))
+            .and_then(Option::as_mut)
+            .ok_or_else(|| anyhow!("The pool has no workers"))?;
+        worker.load += 1;
+        let job = Job {
+            message,
+            id: worker.id,
+            pool: ctx.address().clone(),
+        };
+        // Headers of the routed message are passed to the worker
+        match ctx.headers().cloned() {
+            Some(headers) => worker.address.send_with(job, headers),
+            None => worker.address.send(job),
+        }
+    }
+}
+
+impl<W> Standalone for Pool<W>
+where
+    W: Agent,
+    W::Context: Default,
+{
+}
+
+impl<W> Supervisor for Pool<W>
+where
+    W: Agent,
+    W::Context: Default,
+{
+    type BasedOn = AgentSession<Self>;
+    type GroupBy = ();
+
+    fn finished(&mut self, rel: &Relation<Self>, ctx: &mut Context<Self>) {
+        let Some(slot) = self.slots.remove(&rel.id) else {
+            return;
+        };
+        if ctx.tracker.is_terminating() {
+            return;
+        }
+        let replace = self
+            .workers
+            .get(slot)
+            .and_then(Option::as_ref)
+            .is_some_and(|worker| worker.id == rel.id);
+        if !replace {
+            return;
+        }
+        self.workers[slot] = None;
+        let err = anyhow!("The worker {slot} of the pool has finished");
+        match self.restarts.next_delay(err) {
+            Ok(delay) => {
+                log::debug!("Replacing the worker {slot} of the pool in {delay:?}");
+                let pool = ctx.address().clone();
+                crb_core::spawn(async move {
+                    sleep(delay).await;
+                    pool.send(Restart { slot }).ok();
+                });
+            }
+            Err(err) => {
+                log::warn!("{err}, restarts are exhausted");
+            }
+        }
+    }
+}
+
+impl<W> Agent for Pool<W>
+where
+    W: Agent,
+    W::Context: Default,
+{
+    type Context = SupervisorSession<Self>;
+
+    fn initialize(&mut self, ctx: &mut Context<Self>) -> Next<Self> {
+        self.resize(self.size, ctx);
+        Next::events()
+    }
+}
+
+#[async_trait]
+impl<W, E> OnEvent<Routed<E>> for Pool<W>
+where
+    W: OnEvent<E>,
+    W::Context: Default,
+    E: TheEvent,
+{
+    async fn handle(&mut self, event: Routed<E>, ctx: &mut Context<Self>) -> Result<()> {
+        self.route(Event::new(event.0), None, ctx)
+    }
+}
+
+#[async_trait]
+impl<W, R> OnRequest<R> for Pool<W>
+where
+    W: OnRequest<R>,
+    W::Context: Default,
+    R: Request,
+{
+    async fn handle(&mut self, msg: Interaction<R>, ctx: &mut Context<Self>) -> Result<()> {
+        self.route(msg, None, ctx)
+    }
+}
+
+pub trait PoolExt<W: Agent> {
+    /// Changes the number of workers.
+    fn resize(&self, size: usize) -> Result<()>;
+
+    /// Sends an event to a worker chosen by the routing strategy.
+    fn route<E>(&self, event: E) -> Result<()>
+    where
+        W: OnEvent<E>,
+        E: TheEvent;
+
+    /// Sends an event to the worker that owns the key.
+    fn event_by_key<E>(&self, key: impl Hash, event: E) -> Result<()>
+    where
+        W: OnEvent<E>,
+        E: TheEvent;
+
+    /// Sends a request to the worker that owns the key.
+    fn interact_by_key<R>(&self, key: impl Hash, request: R) -> Fetcher<R::Response>
+    where
+        W: OnRequest<R>,
+        R: Request;
+}
+
+impl<W> PoolExt<W> for Address<Pool<W>>
+where
+    W: Agent,
+    W::Context: Default,
+{
+    fn resize(&self, size: usize) -> Result<()> {
+        self.send(Resize { size })
+    }
+
+    fn route<E>(&self, event: E) -> Result<()>
+    where
+        W: OnEvent<E>,
+        E: TheEvent,
+    {
+        self.event(Routed(event))
+    }
+
+    fn event_by_key<E>(&self, key: impl Hash, event: E) -> Result<()>
+    where
+        W: OnEvent<E>,
+        E: TheEvent,
+    {
+        let message = Event::new(event);
+        let key = hash_of(key);
+        self.send(Keyed { key, message })
+    }
+
+    fn interact_by_key<R>(&self, key: impl Hash, request: R) -> Fetcher<R::Response>
+    where
+        W: OnRequest<R>,
+        R: Request,
+    {
+        let (interplay, fetcher) = Interplay::new_pair(request);
+        let message = Interaction { interplay };
+        let key = hash_of(key);
+        let res = self.send(Keyed { key, message });
+        fetcher.grasp(res)
+    }
+}
+
+fn hash_of(value: impl Hash) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+struct Resize {
+    size: usize,
+}
+
+#[async_trait]
+impl<W> MessageFor<Pool<W>> for Resize
+where
+    W: Agent,
+    W::Context: Default,
+{
+    async fn handle(
+        self: Box<Self>,
+        agent: &mut Pool<W>,
+        ctx: &mut Context<Pool<W>>,
+    ) -> Result<()> {
+        if !ctx.tracker.is_terminating() {
+            agent.resize(self.size, ctx);
+        }
+        Ok(())
+    }
+}
+
+struct Restart {
+    slot: usize,
+}
+
+#[async_trait]
+impl<W> MessageFor<Pool<W>> for Restart
+where
+    W: Agent,
+    W::Context: Default,
+{
+    async fn handle(
+        self: Box<Self>,
+        agent: &mut Pool<W>,
+        ctx: &mut Context<Pool<W>>,
+    ) -> Result<()> {
+        // The slot could be removed or filled by resizing
+        let vacant = agent.workers.get(self.slot).is_some_and(Option::is_none);
+        if vacant && !ctx.tracker.is_terminating() {
+            agent.spawn_worker(self.slot, ctx);
+        }
+        Ok(())
+    }
+}
+
+struct Keyed<M> {
+    key: u64,
+    message: M,
+}
+
+#[async_trait]
+impl<W, M> MessageFor<Pool<W>> for Keyed<M>
+where
+    W: Agent,
+    W::Context: Default,
+    M: MessageFor<W>,
+{
+    async fn handle(
+        self: Box<Self>,
+        agent: &mut Pool<W>,
+        ctx: &mut Context<Pool<W>>,
+    ) -> Result<()> {
+        agent.route(self.message, Some(self.key), ctx)
+    }
+}
+
+/// A message for a worker that reports to the pool when it's handled.
+struct Job<W, M>
+where
+    W: Agent,
+    W::Context: Default,
+{
+    message: M,
+    id: ActivityId,
+    pool: Address<Pool<W>>,
+}
+
+#[async_trait]
+impl<W, M> MessageFor<W> for Job<W, M>
+where
+    W: Agent,
+    W::Context: Default,
+    M: MessageFor<W>,
+{
+    async fn handle(self: Box<Self>, agent: &mut W, ctx: &mut Context<W>) -> Result<()> {
+        let result = Box::new(self.message).handle(agent, ctx).await;
+        self.pool.send(Done { id: self.id }).ok();
+        result
+    }
+}
+
+struct Done {
+    id: ActivityId,
+}
+
+#[async_trait]
+impl<W> MessageFor<Pool<W>> for Done
+where
+    W: Agent,
+    W::Context: Default,
+{
+    async fn handle(
+        self: Box<Self>,
+        agent: &mut Pool<W>,
+        _ctx: &mut Context<Pool<W>>,
+    ) -> Result<()> {
+        let worker = agent
+            .slots
+            .get(&self.id)
+            .and_then(|slot| agent.workers.get_mut(*slot))
+            .and_then(Option::as_mut)
+            .filter(|worker| worker.id == self.id);
+        if let Some(worker) = worker {
+            worker.load = worker.load.saturating_sub(1);
+            agent.restarts.reset();
+        }
+        Ok(())
+    }
+}
@@ -1,4 +1,5 @@
 pub mod interval;
+#[allow(clippy::module_inception)]
 pub mod timer;
 
 pub use interval::*;
@@ -33,6 +33,12 @@ pub struct Timer {
     stream: Option<TimerStream>,
 }
 
+impl Default for Timer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Timer {
     pub fn new() -> Self {
         let (tx, rx) = mpsc::unbounded_channel();
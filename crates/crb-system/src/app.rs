@@ -0,0 +1,304 @@
+use crate::config::{Config, Section, Topology};
+use crate::shutdown::{Exit, Shutdown};
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use crb_agent::{Address, Agent, AgentSession, Context, MessageFor, Next, OnEvent, Standalone};
+use crb_runtime::ManagedContext;
+use crb_superagent::{Relation, Supervisor, SupervisorSession};
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+use std::sync::Arc;
+
+type Apply = Box<dyn FnOnce() -> Result<()> + Send>;
+type Prepared<A> = Box<dyn FnOnce(&Address<A>) -> Result<()> + Send>;
+type Delivery<A> = Box<dyn Fn(&Config, &str) -> Result<Prepared<A>> + Send + Sync>;
+
+/// Creates agents of a type and delivers config sections to them.
+pub struct Factory<A: Agent> {
+    make: Box<dyn Fn() -> A + Send + Sync>,
+    deliveries: Vec<Delivery<A>>,
+}
+
+impl<A> Factory<A>
+where
+    A: Agent,
+    A::Context: Default,
+{
+    pub fn new<F>(make: F) -> Self
+    where
+        F: Fn() -> A + Send + Sync + 'static,
+    {
+        Self {
+            make: Box::new(make),
+            deliveries: Vec::new(),
+        }
+    }
+
+    /// Requests the section that is delivered as an event
+    /// after the agent is spawned and when the config is reloaded.
+    pub fn section<S>(mut self) -> Self
+    where
+        A: OnEvent<S>,
+        S: Section,
+    {
+        let delivery: Delivery<A> = Box::new(|config, name| {
+            let section = config.section::<S>(Some(name))?;
+            Ok(Box::new(move |address: &Address<A>| address.event(section)))
+        });
+        self.deliveries.push(delivery);
+        self
+    }
+
+    fn prepare(&self, config: &Config, name: &str) -> Result<Vec<Prepared<A>>> {
+        self.deliveries
+            .iter()
+            .map(|delivery| delivery(config, name))
+            .collect()
+    }
+}
+
+trait Spawner: Send + Sync {
+    fn validate(&self, config: &Config, name: &str) -> Result<()>;
+
+    fn spawn(self: Arc<Self>, group: usize, ctx: &mut Context<Root>) -> Box<dyn Reconfigure>;
+}
+
+impl<A> Spawner for Factory<A>
+where
+    A: Agent,
+    A::Context: Default,
+{
+    fn validate(&self, config: &Config, name: &str) -> Result<()> {
+        self.prepare(config, name).map(drop)
+    }
+
+    fn spawn(self: Arc<Self>, group: usize, ctx: &mut Context<Root>) -> Box<dyn Reconfigure> {
+        let (address, _rel) = ctx.spawn_agent((self.make)(), group);
+        Box::new(Spawned {
+            address,
+            factory: self,
+        })
+    }
+}
+
+trait Reconfigure: Send {
+    fn prepare(&self, config: &Config, name: &str) -> Result<Vec<Apply>>;
+}
+
+struct Spawned<A: Agent> {
+    address: Address<A>,
+    factory: Arc<Factory<A>>,
+}
+
+impl<A> Reconfigure for Spawned<A>
+where
+    A: Agent,
+    A::Context: Default,
+{
+    fn prepare(&self, config: &Config, name: &str) -> Result<Vec<Apply>> {
+        let prepared = self.factory.prepare(config, name)?;
+        let applies = prepared
+            .into_iter()
+            .map(|prepared| {
+                let address = self.address.clone();
+                Box::new(move || prepared(&address)) as Apply
+            })
+            .collect();
+        Ok(applies)
+    }
+}
+
+enum Source {
+    File(PathBuf),
+    Static(Config),
+}
+
+/// Bootstraps agents declared in a config file.
+///
+/// Agents are listed in the `[agents.<name>]` tables with the `factory`
+/// and `group` fields. The `groups` field sets the order of groups.
+/// SIGHUP reloads the config file and delivers sections again.
+pub struct App {
+    factories: HashMap<String, Arc<dyn Spawner>>,
+    source: Source,
+    shutdown: Shutdown,
+}
+
+impl Default for App {
+    fn default() -> Self {
+        Self {
+            factories: HashMap::new(),
+            source: Source::Static(Config::default()),
+            shutdown: Shutdown::default(),
+        }
+    }
+}
+
+impl App {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn config_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.source = Source::File(path.into());
+        self
+    }
+
+    pub fn config(mut self, config: Config) -> Self {
+        self.source = Source::Static(config);
+        self
+    }
+
+    pub fn register<A>(mut self, name: impl Into<String>, factory: Factory<A>) -> Self
+    where
+        A: Agent,
+        A::Context: Default,
+    {
+        self.factories.insert(name.into(), Arc::new(factory));
+        self
+    }
+
+    pub fn with_shutdown(mut self, shutdown: Shutdown) -> Self {
+        self.shutdown = shutdown;
+        self
+    }
+
+    /// Spawns agents and waits for them or for a shutdown signal.
+    pub async fn run(self) -> Result<Exit> {
+        let config = match &self.source {
+            Source::File(path) => Config::load(path).await?,
+            Source::Static(config) => config.clone(),
+        };
+        let topology = config.topology()?;
+        let mut agents = Vec::new();
+        for (name, spec) in &topology.agents {
+            let spawner = self.factories.get(&spec.factory).ok_or_else(|| {
+                anyhow!("Factory {} of agent {name} is not registered", spec.factory)
+            })?;
+            let group = topology
+                .groups
+                .iter()
+                .position(|group| *group == spec.group)
+                .ok_or_else(|| anyhow!("Group {} of agent {name} is not declared", spec.group))?;
+            spawner.validate(&config, name)?;
+            agents.push((name.clone(), group, spawner.clone()));
+        }
+        agents.sort_by_key(|(_, group, _)| *group);
+        let root = Root {
+            agents,
+            topology,
+            config,
+            live: BTreeMap::new(),
+        };
+        let address = root.spawn();
+        let source = self.source;
+        let reloader = address.clone();
+        let on_hangup = move || {
+            if let Source::File(path) = &source {
+                let path = path.clone();
+                let reloader = reloader.clone();
+                tokio::spawn(async move {
+                    match Config::load(&path).await {
+                        Ok(config) => {
+                            reloader.send(Reload { config }).ok();
+                        }
+                        Err(err) => {
+                            log::error!("Can't reload the config {}: {err}", path.display());
+                        }
+                    }
+                });
+            }
+            true
+        };
+        self.shutdown.drive(address, on_hangup).await
+    }
+}
+
+/// The root supervisor of an app.
+struct Root {
+    agents: Vec<(String, usize, Arc<dyn Spawner>)>,
+    topology: Topology,
+    config: Config,
+    live: BTreeMap<String, Box<dyn Reconfigure>>,
+}
+
+impl Standalone for Root {}
+
+impl Supervisor for Root {
+    type BasedOn = AgentSession<Self>;
+    type GroupBy = usize;
+
+    fn finished(&mut self, _rel: &Relation<Self>, ctx: &mut Context<Self>) {
+        if ctx.tracker.is_empty() {
+            ctx.shutdown();
+        }
+    }
+}
+
+impl Agent for Root {
+    type Context = SupervisorSession<Self>;
+
+    fn initialize(&mut self, ctx: &mut Context<Self>) -> Next<Self> {
+        for (name, group, spawner) in self.agents.drain(..) {
+            let spawned = spawner.spawn(group, ctx);
+            self.live.insert(name, spawned);
+        }
+        if self.live.is_empty() {
+            return Next::done();
+        }
+        if let Err(err) = self.apply(self.config.clone()) {
+            return Next::fail(err);
+        }
+        Next::events()
+    }
+}
+
+impl Root {
+    /// Delivers sections to all agents if all of them are valid.
+    ///
+    /// Sections are delivered to all running agents even if some
+    /// of them can't receive them, failed agents are reported together.
+    fn apply(&mut self, config: Config) -> Result<()> {
+        let mut applies = Vec::new();
+        for (name, spawned) in &self.live {
+            let prepared = spawned
+                .prepare(&config, name)
+                .map_err(|err| anyhow!("Config of agent {name} is not applied: {err}"))?;
+            applies.extend(prepared.into_iter().map(|apply| (name.as_str(), apply)));
+        }
+        let mut failed = Vec::new();
+        for (name, apply) in applies {
+            if let Err(err) = apply() {
+                log::error!("Config can't be delivered to agent {name}: {err}");
+                if !failed.contains(&name) {
+                    failed.push(name);
+                }
+            }
+        }
+        let result = if failed.is_empty() {
+            Ok(())
+        } else {
+            Err(anyhow!(
+                "Config is not delivered to agents: {}",
+                failed.join(", ")
+            ))
+        };
+        self.config = config;
+        result
+    }
+}
+
+struct Reload {
+    config: Config,
+}
+
+#[async_trait]
+impl MessageFor<Root> for Reload {
+    async fn handle(self: Box<Self>, agent: &mut Root, _ctx: &mut Context<Root>) -> Result<()> {
+        let topology = self.config.topology()?;
+        if topology != agent.topology {
+            log::warn!("Changes of agents or groups require a restart");
+        }
+        agent.apply(self.config)
+    }
+}
@@ -0,0 +1,142 @@
+use anyhow::{anyhow, Error, Result};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// A typed section of a config.
+///
+/// An agent's own section `[agents.<name>.<NAME>]` overrides
+/// the common section `[<NAME>]`.
+pub trait Section: DeserializeOwned + Send + 'static {
+    const NAME: &'static str;
+
+    /// Checks values that can't be checked by deserialization.
+    fn validate(&self) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// A parsed config file.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Config {
+    value: Value,
+}
+
+impl Config {
+    pub fn from_toml(text: &str) -> Result<Self> {
+        let value = toml::from_str(text)?;
+        Ok(Self { value })
+    }
+
+    pub fn from_json(text: &str) -> Result<Self> {
+        let value = serde_json::from_str(text)?;
+        Ok(Self { value })
+    }
+
+    /// Loads a config file. The format is detected by the extension.
+    pub async fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let text = tokio::fs::read_to_string(path).await?;
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("toml") => Self::from_toml(&text),
+            Some("json") => Self::from_json(&text),
+            _ => Err(anyhow!("Unsupported config format: {}", path.display())),
+        }
+    }
+
+    /// Raw value of the section for the agent.
+    pub fn raw_section(&self, name: &str, agent: Option<&str>) -> Option<&Value> {
+        agent
+            .and_then(|agent| self.value.get("agents")?.get(agent)?.get(name))
+            .or_else(|| self.value.get(name))
+    }
+
+    pub fn section<S: Section>(&self, agent: Option<&str>) -> Result<S> {
+        let value = self
+            .raw_section(S::NAME, agent)
+            .ok_or_else(|| anyhow!("The section [{}] is not found", S::NAME))?;
+        S::deserialize(value)
+            .map_err(Error::from)
+            .and_then(|section| section.validate().map(|_| section))
+            .map_err(|err| anyhow!("Invalid section [{}]: {err}", S::NAME))
+    }
+
+    /// Keeps the value of the section for the agent from the `old` config.
+    ///
+    /// The value is restored in the table it came from: the agent's own section
+    /// or the common section if the agent had no own section.
+    pub(crate) fn restore_section(&mut self, old: &Config, name: &str, agent: Option<&str>) {
+        let own = agent.and_then(|agent| old.value.get("agents")?.get(agent)?.get(name));
+        if agent.is_some() {
+            self.set_raw_section(name, agent, own.cloned());
+        }
+        if own.is_none() {
+            self.set_raw_section(name, None, old.value.get(name).cloned());
+        }
+    }
+
+    fn set_raw_section(&mut self, name: &str, agent: Option<&str>, value: Option<Value>) {
+        let mut table = Some(&mut self.value);
+        if let Some(agent) = agent {
+            table = table
+                .and_then(|value| value.get_mut("agents"))
+                .and_then(|agents| agents.get_mut(agent));
+        }
+        let Some(Value::Object(table)) = table else {
+            return;
+        };
+        match value {
+            Some(value) => {
+                table.insert(name.into(), value);
+            }
+            None => {
+                table.remove(name);
+            }
+        }
+    }
+
+    pub(crate) fn topology(&self) -> Result<Topology> {
+        match self.value.get("agents") {
+            Some(_) => Ok(Topology::deserialize(&self.value)?),
+            None => Ok(Topology::default()),
+        }
+    }
+}
+
+fn default_groups() -> Vec<String> {
+    vec![DEFAULT_GROUP.into()]
+}
+
+fn default_group() -> String {
+    DEFAULT_GROUP.into()
+}
+
+const DEFAULT_GROUP: &str = "default";
+
+/// Agents to spawn and the order of groups.
+/// Groups are terminated in the reverse order.
+#[derive(Debug, PartialEq, Deserialize)]
+pub(crate) struct Topology {
+    #[serde(default = "default_groups")]
+    pub groups: Vec<String>,
+    #[serde(default)]
+    pub agents: BTreeMap<String, AgentSpec>,
+}
+
+impl Default for Topology {
+    fn default() -> Self {
+        Self {
+            groups: default_groups(),
+            agents: BTreeMap::new(),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Deserialize)]
+pub(crate) struct AgentSpec {
+    pub factory: String,
+    #[serde(default = "default_group")]
+    pub group: String,
+}
@@ -1,3 +1,20 @@
+#[cfg(feature = "config")]
+pub mod app;
+#[cfg(feature = "config")]
+pub mod config;
+pub mod shutdown;
+mod signals;
+#[cfg(feature = "config")]
+pub mod watcher;
+
+#[cfg(feature = "config")]
+pub use app::{App, Factory};
+#[cfg(feature = "config")]
+pub use config::{Config, Section};
+pub use shutdown::{Exit, Shutdown};
+#[cfg(feature = "config")]
+pub use watcher::{ConfigChanged, ConfigRejected, ConfigSection, ConfigWatcher};
+
 use anyhow::Result;
 use async_trait::async_trait;
 use crb_agent::{Address, Agent};
@@ -7,6 +24,9 @@ use tokio::{select, signal};
 #[async_trait]
 pub trait Main {
     async fn join_or_signal(self) -> Result<()>;
+
+    /// Waits for the agent and stops it gracefully on SIGTERM, SIGINT or SIGHUP.
+    async fn join_or_shutdown(self, shutdown: Shutdown) -> Result<Exit>;
 }
 
 #[async_trait]
@@ -30,4 +50,8 @@ where
         }
         Ok(())
     }
+
+    async fn join_or_shutdown(self, shutdown: Shutdown) -> Result<Exit> {
+        shutdown.drive(self, || false).await
+    }
 }
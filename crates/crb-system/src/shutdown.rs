@@ -0,0 +1,174 @@
+use crate::signals::{Signal, Signals};
+use anyhow::Result;
+use crb_agent::{Address, Agent};
+use crb_runtime::{InterruptionLevel, Interruptor};
+use futures::future::BoxFuture;
+use std::future::Future;
+use std::process::{ExitCode, Termination};
+use tokio::select;
+use tokio::time::{sleep_until, Duration, Instant};
+
+type Hook = Box<dyn FnOnce() -> BoxFuture<'static, Result<()>> + Send>;
+
+/// The configuration of a graceful shutdown.
+///
+/// A signal interrupts an agent with the `EVENT` level. If the agent
+/// is not finished before the deadline of a level, the level is escalated.
+/// When the `EXIT` level is reached, the agent is abandoned.
+pub struct Shutdown {
+    pub(crate) deadlines: Vec<(InterruptionLevel, Duration)>,
+    pub(crate) hook: Option<Hook>,
+}
+
+impl Default for Shutdown {
+    fn default() -> Self {
+        Self {
+            deadlines: vec![
+                (InterruptionLevel::EVENT, Duration::from_secs(30)),
+                (InterruptionLevel::FLAG, Duration::from_secs(10)),
+                (InterruptionLevel::ABORT, Duration::from_secs(5)),
+            ],
+            hook: None,
+        }
+    }
+}
+
+impl Shutdown {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the time given to an agent to finish after an interruption
+    /// with the level.
+    pub fn with_deadline(mut self, level: InterruptionLevel, deadline: Duration) -> Self {
+        match self.deadlines.iter_mut().find(|(l, _)| *l == level) {
+            Some((_, current)) => *current = deadline,
+            None => {
+                self.deadlines.push((level, deadline));
+                self.deadlines.sort_by_key(|(l, _)| *l);
+            }
+        }
+        self
+    }
+
+    /// Sets a hook that is called when the agent is finished
+    /// or abandoned. Use it to flush a state.
+    pub fn on_shutdown<F, Fut>(mut self, hook: F) -> Self
+    where
+        F: FnOnce() -> Fut + Send + 'static,
+        Fut: Future<Output = Result<()>> + Send + 'static,
+    {
+        self.hook = Some(Box::new(move || Box::pin(hook())));
+        self
+    }
+
+    pub(crate) fn deadline(&self, level: InterruptionLevel) -> Option<Duration> {
+        self.deadlines
+            .iter()
+            .find(|(l, _)| *l == level)
+            .map(|(_, deadline)| *deadline)
+    }
+
+    /// Waits for the agent and stops it on a signal.
+    ///
+    /// `on_hangup` is called for SIGHUP before the shutdown has started.
+    /// If it returns `false`, the signal starts the shutdown.
+    pub(crate) async fn drive<A, F>(
+        mut self,
+        mut address: Address<A>,
+        mut on_hangup: F,
+    ) -> Result<Exit>
+    where
+        A: Agent,
+        A::Context: Default,
+        F: FnMut() -> bool + Send,
+    {
+        let mut signals = Signals::new()?;
+        let mut level: Option<InterruptionLevel> = None;
+        let mut escalated = false;
+        let mut deadline = None;
+        let exit = loop {
+            let timeout = async {
+                match deadline {
+                    Some(deadline) => sleep_until(deadline).await,
+                    None => futures::future::pending().await,
+                }
+            };
+            let expired = select! {
+                signal = signals.recv() => {
+                    if signal == Signal::Hangup && level.is_none() && on_hangup() {
+                        continue;
+                    }
+                    false
+                }
+                _ = timeout => true,
+                status = address.join() => {
+                    status?;
+                    break if level.is_none() {
+                        Exit::Done
+                    } else if escalated {
+                        Exit::Escalated
+                    } else {
+                        Exit::Interrupted
+                    };
+                }
+            };
+            escalated |= level.is_some();
+            let next = level.map_or(InterruptionLevel::EVENT, |level| level.next());
+            if expired && level == Some(next) {
+                break Exit::Abandoned;
+            }
+            level = Some(next);
+            address.interrupt_with_level(next);
+            match self.deadline(next) {
+                Some(timeout) => {
+                    deadline = Some(Instant::now() + timeout);
+                }
+                None if next >= InterruptionLevel::EXIT => {
+                    break Exit::Abandoned;
+                }
+                None => {
+                    deadline = None;
+                }
+            }
+        };
+        if let Some(hook) = self.hook.take() {
+            hook().await?;
+        }
+        Ok(exit)
+    }
+}
+
+/// How an agent has ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Exit {
+    /// The agent has finished by itself.
+    Done,
+    /// The agent has been stopped by a signal in time.
+    Interrupted,
+    /// The agent has been stopped, but a deadline was missed.
+    Escalated,
+    /// The agent hasn't stopped before the last deadline.
+    Abandoned,
+}
+
+impl Exit {
+    pub fn code(&self) -> u8 {
+        match self {
+            Self::Done | Self::Interrupted => 0,
+            Self::Escalated => 1,
+            Self::Abandoned => 2,
+        }
+    }
+
+    /// Terminates the process with the code of the exit.
+    pub fn exit(self) -> ! {
+        std::process::exit(self.code().into())
+    }
+}
+
+impl Termination for Exit {
+    fn report(self) -> ExitCode {
+        ExitCode::from(self.code())
+    }
+}
@@ -0,0 +1,274 @@
+use crate::config::{Config, Section};
+use anyhow::Result;
+use async_trait::async_trait;
+use crb_agent::{
+    Address, Agent, Context, DoAsync, Next, OnEvent, Standalone, ToAddress, ToRecipient,
+};
+use crb_core::{time::Duration, Slot, Unique};
+use crb_runtime::ManagedContext;
+use crb_send::{Recipient, Sender};
+use crb_superagent::{
+    ManageSubscription, StreamSession, SubscribersExt, Subscription, Timeout, Timer,
+};
+use notify::{recommended_watcher, EventHandler, RecommendedWatcher, RecursiveMode, Watcher};
+use std::any::TypeId;
+use std::collections::HashMap;
+use std::marker::PhantomData;
+use std::path::{Path, PathBuf};
+
+const DEBOUNCE_MS: u64 = 100;
+
+/// A subscription to a section of the watched config.
+///
+/// The current section is the state of the subscription,
+/// and changes of it are delivered as `ConfigChanged` updates.
+pub struct ConfigSection<T> {
+    agent: Option<String>,
+    _section: PhantomData<fn() -> T>,
+}
+
+impl<T> Default for ConfigSection<T> {
+    fn default() -> Self {
+        Self {
+            agent: None,
+            _section: PhantomData,
+        }
+    }
+}
+
+impl<T> ConfigSection<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Prefers the section of the agent from the `[agents.<name>]` table.
+    pub fn for_agent(name: impl Into<String>) -> Self {
+        Self {
+            agent: Some(name.into()),
+            _section: PhantomData,
+        }
+    }
+}
+
+impl<T: Section> Subscription for ConfigSection<T> {
+    type State = T;
+    type Update = ConfigChanged<T>;
+}
+
+/// A new value of a section that has passed validation.
+#[derive(Debug, Clone)]
+pub struct ConfigChanged<T> {
+    pub section: T,
+}
+
+/// A change of the config that has not been applied.
+#[derive(Debug, Clone)]
+pub struct ConfigRejected {
+    /// `None` if the whole file can't be loaded.
+    pub section: Option<&'static str>,
+    pub agent: Option<String>,
+    pub error: String,
+}
+
+type Refresh = fn(&Config, &Config, &mut Context<ConfigWatcher>) -> Vec<ConfigRejected>;
+
+/// Reloads a config file when it changes and delivers changed sections
+/// to subscribers.
+pub struct ConfigWatcher {
+    path: PathBuf,
+    config: Config,
+    watcher: Slot<RecommendedWatcher>,
+    debouncer: Timer,
+    refreshers: HashMap<TypeId, Refresh>,
+    observers: Vec<Recipient<ConfigRejected>>,
+}
+
+impl ConfigWatcher {
+    pub async fn new(path: impl Into<PathBuf>) -> Result<Self> {
+        let path = path.into();
+        let config = Config::load(&path).await?;
+        Ok(Self {
+            path,
+            config,
+            watcher: Slot::empty(),
+            debouncer: Timer::new(),
+            refreshers: HashMap::new(),
+            observers: Vec::new(),
+        })
+    }
+
+    pub fn report_to(&mut self, recipient: impl ToRecipient<ConfigRejected>) {
+        self.observers.push(recipient.to_recipient());
+    }
+
+    fn reject(&self, rejected: ConfigRejected) {
+        log::error!(
+            "Config change of {} is rejected: {}",
+            self.path.display(),
+            rejected.error
+        );
+        for observer in &self.observers {
+            observer.send(rejected.clone()).ok();
+        }
+    }
+}
+
+impl Standalone for ConfigWatcher {}
+
+impl Agent for ConfigWatcher {
+    type Context = StreamSession<Self>;
+
+    fn begin(&mut self) -> Next<Self> {
+        Next::do_async(Initialize)
+    }
+
+    fn interrupt(&mut self, ctx: &mut Context<Self>) {
+        self.watcher.take().ok();
+        self.debouncer.cancel().ok();
+        ctx.shutdown();
+    }
+}
+
+struct Initialize;
+
+#[async_trait]
+impl DoAsync<Initialize> for ConfigWatcher {
+    async fn handle(&mut self, _: Initialize, ctx: &mut Context<Self>) -> Result<Next<Self>> {
+        ctx.consume(self.debouncer.events()?);
+        let forwarder = EventsForwarder {
+            address: ctx.to_address(),
+            file_name: self.path.file_name().map(ToOwned::to_owned),
+        };
+        let mut watcher = recommended_watcher(forwarder)?;
+        // Editors often replace files, so the directory is watched.
+        let dir = match self.path.parent() {
+            Some(dir) if dir != Path::new("") => dir,
+            _ => Path::new("."),
+        };
+        watcher.watch(dir, RecursiveMode::NonRecursive)?;
+        self.watcher.fill(watcher)?;
+        Ok(Next::events())
+    }
+}
+
+struct EventsForwarder {
+    address: Address<ConfigWatcher>,
+    file_name: Option<std::ffi::OsString>,
+}
+
+impl EventHandler for EventsForwarder {
+    fn handle_event(&mut self, event: EventResult) {
+        let relevant = match &event {
+            Ok(event) => event
+                .paths
+                .iter()
+                .any(|path| path.file_name() == self.file_name.as_deref()),
+            Err(_) => true,
+        };
+        if relevant {
+            self.address.event(event).ok();
+        }
+    }
+}
+
+type EventResult = Result<notify::Event, notify::Error>;
+
+#[async_trait]
+impl OnEvent<EventResult> for ConfigWatcher {
+    async fn handle(&mut self, result: EventResult, _ctx: &mut Context<Self>) -> Result<()> {
+        let _event = result?;
+        let duration = Duration::from_millis(DEBOUNCE_MS);
+        self.debouncer.schedule(duration)?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl OnEvent<Timeout> for ConfigWatcher {
+    async fn handle(&mut self, _: Timeout, ctx: &mut Context<Self>) -> Result<()> {
+        self.debouncer.cancel()?;
+        let config = match Config::load(&self.path).await {
+            Ok(config) => config,
+            Err(err) => {
+                let rejected = ConfigRejected {
+                    section: None,
+                    agent: None,
+                    error: err.to_string(),
+                };
+                self.reject(rejected);
+                return Ok(());
+            }
+        };
+        if config == self.config {
+            return Ok(());
+        }
+        let mut committed = config.clone();
+        let refreshers: Vec<Refresh> = self.refreshers.values().copied().collect();
+        for refresh in refreshers {
+            for rejected in refresh(&self.config, &config, ctx) {
+                // Rejected sections keep their old values
+                if let Some(section) = rejected.section {
+                    committed.restore_section(&self.config, section, rejected.agent.as_deref());
+                }
+                self.reject(rejected);
+            }
+        }
+        self.config = committed;
+        Ok(())
+    }
+}
+
+/// Delivers changed sections of the type to subscribers
+/// and returns rejected sections once per agent.
+fn refresh<T: Section>(
+    old: &Config,
+    new: &Config,
+    ctx: &mut Context<ConfigWatcher>,
+) -> Vec<ConfigRejected> {
+    let mut rejected = Vec::new();
+    ctx.subscribers::<ConfigSection<T>>().broadcast_with(|sub| {
+        let agent = sub.agent.as_deref();
+        if old.raw_section(T::NAME, agent) == new.raw_section(T::NAME, agent) {
+            return None;
+        }
+        match new.section::<T>(agent) {
+            Ok(section) => Some(ConfigChanged { section }),
+            Err(err) => {
+                if !rejected
+                    .iter()
+                    .any(|rejected: &ConfigRejected| rejected.agent == sub.agent)
+                {
+                    rejected.push(ConfigRejected {
+                        section: Some(T::NAME),
+                        agent: sub.agent.clone(),
+                        error: err.to_string(),
+                    });
+                }
+                None
+            }
+        }
+    });
+    rejected
+}
+
+#[async_trait]
+impl<T: Section> ManageSubscription<ConfigSection<T>> for ConfigWatcher {
+    async fn subscribe(
+        &mut self,
+        sub_id: Unique<ConfigSection<T>>,
+        _ctx: &mut Context<Self>,
+    ) -> Result<T> {
+        self.refreshers
+            .entry(TypeId::of::<T>())
+            .or_insert(refresh::<T>);
+        self.config.section(sub_id.agent.as_deref())
+    }
+
+    async fn unsubscribe(
+        &mut self,
+        _sub_id: Unique<ConfigSection<T>>,
+        _ctx: &mut Context<Self>,
+    ) -> Result<()> {
+        Ok(())
+    }
+}
@@ -0,0 +1,77 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use crb::agent::{Agent, AgentSession, Context, ManagedContext, OnEvent};
+use crb_system::{App, Config, Exit, Factory, Section};
+use serde::Deserialize;
+use std::sync::{Arc, Mutex};
+
+type Greetings = Arc<Mutex<Vec<String>>>;
+
+struct Greeter {
+    greetings: Greetings,
+}
+
+impl Agent for Greeter {
+    type Context = AgentSession<Self>;
+}
+
+#[derive(Deserialize)]
+struct Greeting {
+    text: String,
+}
+
+impl Section for Greeting {
+    const NAME: &'static str = "greeting";
+}
+
+#[async_trait]
+impl OnEvent<Greeting> for Greeter {
+    async fn handle(&mut self, event: Greeting, ctx: &mut Context<Self>) -> Result<()> {
+        self.greetings.lock().unwrap().push(event.text);
+        ctx.shutdown();
+        Ok(())
+    }
+}
+
+const CONFIG: &str = r#"
+groups = ["early", "late"]
+
+[greeting]
+text = "Hello"
+
+[agents.first]
+factory = "greeter"
+group = "early"
+
+[agents.second]
+factory = "greeter"
+group = "late"
+
+[agents.second.greeting]
+text = "Hi"
+"#;
+
+#[tokio::test]
+async fn test_app() -> Result<()> {
+    let greetings = Greetings::default();
+    let factory = {
+        let greetings = greetings.clone();
+        Factory::new(move || Greeter {
+            greetings: greetings.clone(),
+        })
+        .section::<Greeting>()
+    };
+    let exit = App::new()
+        .config(Config::from_toml(CONFIG)?)
+        .register("greeter", factory)
+        .run()
+        .await?;
+    assert_eq!(exit, Exit::Done);
+    let mut greetings = greetings.lock().unwrap().clone();
+    greetings.sort();
+    assert_eq!(greetings, ["Hello", "Hi"]);
+
+    let unknown = App::new().config(Config::from_toml(CONFIG)?).run().await;
+    assert!(unknown.is_err());
+    Ok(())
+}
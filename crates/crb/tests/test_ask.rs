@@ -0,0 +1,104 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use crb::agent::{Address, Agent, AgentSession, Context, ManagedContext, Next, Standalone};
+use crb::core::time::{sleep, Duration};
+use crb::superagent::{AskExt, FetchError, OnRequest, OnResponse, Output, Request};
+use std::sync::{Arc, Mutex};
+
+struct Calculator;
+
+impl Standalone for Calculator {}
+
+impl Agent for Calculator {
+    type Context = AgentSession<Self>;
+}
+
+struct Add(u32, u32);
+
+impl Request for Add {
+    type Response = u32;
+}
+
+#[async_trait]
+impl OnRequest<Add> for Calculator {
+    async fn on_request(&mut self, request: Add, _ctx: &mut Context<Self>) -> Result<u32> {
+        if request.0 == 0 {
+            sleep(Duration::from_millis(200)).await;
+        }
+        Ok(request.0 + request.1)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum Outcome {
+    Sum(u32),
+    TimedOut,
+    Failed,
+}
+
+struct Caller {
+    calculator: Address<Calculator>,
+    outcomes: Arc<Mutex<Vec<(&'static str, Outcome)>>>,
+}
+
+impl Standalone for Caller {}
+
+impl Agent for Caller {
+    type Context = AgentSession<Self>;
+
+    fn initialize(&mut self, ctx: &mut Context<Self>) -> Next<Self> {
+        // The slow request is the first one, so others wait for it
+        ctx.ask_within(
+            &self.calculator,
+            Add(0, 1),
+            "slow",
+            Duration::from_millis(50),
+        );
+        let canceled = ctx.ask(&self.calculator, Add(1, 1), "canceled");
+        ctx.ask(&self.calculator, Add(2, 3), "sum");
+        assert!(ctx.cancel_ask(canceled));
+        Next::events()
+    }
+}
+
+#[async_trait]
+impl OnResponse<u32, &'static str> for Caller {
+    async fn on_response(
+        &mut self,
+        response: Output<u32>,
+        tag: &'static str,
+        ctx: &mut Context<Self>,
+    ) -> Result<()> {
+        let outcome = match response {
+            Ok(sum) => Outcome::Sum(sum),
+            Err(FetchError::TimedOut) => Outcome::TimedOut,
+            Err(_) => Outcome::Failed,
+        };
+        self.outcomes.lock().unwrap().push((tag, outcome));
+        if ctx.pending_asks() == 0 {
+            ctx.shutdown();
+        }
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn test_ask() -> Result<()> {
+    let mut calculator = Calculator.spawn();
+    let outcomes = Arc::new(Mutex::new(Vec::new()));
+    let mut caller = Caller {
+        calculator: calculator.clone(),
+        outcomes: outcomes.clone(),
+    }
+    .spawn();
+    caller.join().await?;
+    let outcomes = std::mem::take(&mut *outcomes.lock().unwrap());
+    assert_eq!(
+        outcomes,
+        [("slow", Outcome::TimedOut), ("sum", Outcome::Sum(5))]
+    );
+
+    calculator.interrupt()?;
+    calculator.join().await?;
+    Ok(())
+}
@@ -0,0 +1,97 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use crb::agent::{Agent, AgentSession, Context, ManagedContext, Next, OnEvent, Standalone};
+use crb::core::Slot;
+use crb::runtime::InterruptionLevel;
+use crb::superagent::{
+    Assignment, AssignmentFinished, AssignmentStatus, Interplay, OnResponse, Output, Supervisor,
+    SupervisorSession,
+};
+use std::sync::{Arc, Mutex};
+
+type Log = Arc<Mutex<Vec<String>>>;
+
+struct Boss {
+    // Keeps the request unanswered
+    pending: Option<Interplay<(), u32>>,
+    stuck: Slot<Assignment<Self>>,
+    log: Log,
+}
+
+impl Standalone for Boss {}
+
+impl Supervisor for Boss {
+    type BasedOn = AgentSession<Self>;
+    type GroupBy = ();
+}
+
+impl Agent for Boss {
+    type Context = SupervisorSession<Self>;
+
+    fn initialize(&mut self, ctx: &mut Context<Self>) -> Next<Self> {
+        let (interplay, stuck) = Interplay::new_pair(());
+        self.pending = Some(interplay);
+        let stuck = ctx.assign_notified(stuck, (), "stuck");
+        assert_eq!(stuck.status(), AssignmentStatus::Active);
+        self.stuck.fill(stuck).ok();
+
+        let (interplay, quick) = Interplay::new_pair(());
+        interplay.responder.send(7).ok();
+        ctx.assign_notified(quick, (), "quick");
+        Next::events()
+    }
+}
+
+#[async_trait]
+impl OnResponse<u32, &'static str> for Boss {
+    async fn on_response(
+        &mut self,
+        response: Output<u32>,
+        tag: &'static str,
+        _ctx: &mut Context<Self>,
+    ) -> Result<()> {
+        self.log
+            .lock()
+            .unwrap()
+            .push(format!("{tag}: {}", response?));
+        let stuck = self.stuck.get_mut()?;
+        stuck.cancel(InterruptionLevel::ABORT);
+        assert_eq!(stuck.status(), AssignmentStatus::Canceling);
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl OnEvent<AssignmentFinished<&'static str>> for Boss {
+    async fn handle(
+        &mut self,
+        event: AssignmentFinished<&'static str>,
+        ctx: &mut Context<Self>,
+    ) -> Result<()> {
+        self.log
+            .lock()
+            .unwrap()
+            .push(format!("{} finished", event.tag));
+        if event.tag == "stuck" {
+            let status = self.stuck.get_mut()?.join().await?;
+            assert_eq!(status, AssignmentStatus::Canceled);
+            ctx.shutdown();
+        }
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn test_assignment() -> Result<()> {
+    let log = Log::default();
+    let mut boss = Boss {
+        pending: None,
+        stuck: Slot::empty(),
+        log: log.clone(),
+    }
+    .spawn();
+    boss.join().await?;
+    let log = log.lock().unwrap().clone();
+    assert_eq!(log, ["quick: 7", "quick finished", "stuck finished"]);
+    Ok(())
+}
@@ -0,0 +1,114 @@
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use crb::agent::{Agent, AgentSession, Context, OnEvent, Standalone};
+use crb::core::{mpsc, time::timeout, time::Duration};
+use crb::superagent::SubscribeExt;
+use crb_system::{ConfigRejected, ConfigSection, ConfigWatcher, Section};
+use futures::StreamExt;
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize)]
+struct Limits {
+    max: u32,
+}
+
+impl Section for Limits {
+    const NAME: &'static str = "limits";
+
+    fn validate(&self) -> Result<()> {
+        if self.max == 0 {
+            return Err(anyhow!("max must be positive"));
+        }
+        Ok(())
+    }
+}
+
+struct Alerts {
+    tx: mpsc::UnboundedSender<ConfigRejected>,
+}
+
+impl Standalone for Alerts {}
+
+impl Agent for Alerts {
+    type Context = AgentSession<Self>;
+}
+
+#[async_trait]
+impl OnEvent<ConfigRejected> for Alerts {
+    async fn handle(&mut self, event: ConfigRejected, _ctx: &mut Context<Self>) -> Result<()> {
+        self.tx.send(event)?;
+        Ok(())
+    }
+}
+
+const WAIT: Duration = Duration::from_secs(5);
+
+#[tokio::test]
+async fn test_config_watcher() -> Result<()> {
+    let dir = std::env::temp_dir().join(format!("crb-config-watcher-{}", std::process::id()));
+    std::fs::create_dir_all(&dir)?;
+    let path = dir.join("service.toml");
+    std::fs::write(&path, "[limits]\nmax = 1\n")?;
+
+    let (tx, mut rejected) = mpsc::unbounded_channel();
+    let mut alerts = Alerts { tx }.spawn();
+    let mut watcher = ConfigWatcher::new(&path).await?;
+    watcher.report_to(&alerts);
+    let mut address = watcher.spawn();
+
+    // The watcher handles subscriptions when it has started watching
+    let state_entry = address.subscribe(ConfigSection::<Limits>::new()).await?;
+    assert_eq!(state_entry.state.max, 1);
+    let mut entry = state_entry.entry;
+    let _other = address.subscribe(ConfigSection::<Limits>::new()).await?;
+
+    std::fs::write(&path, "[limits]\nmax = 0\n")?;
+    let event = timeout(WAIT, rejected.recv()).await?.unwrap();
+    assert_eq!(event.section, Some("limits"));
+    // The rejected section keeps the old value
+    let late = address.subscribe(ConfigSection::<Limits>::new()).await?;
+    assert_eq!(late.state.max, 1);
+
+    std::fs::write(&path, "[limits]\nmax = 5\n")?;
+    let changed = timeout(WAIT, entry.next()).await?.unwrap();
+    assert_eq!(changed.section.max, 5);
+
+    address.interrupt()?;
+    address.join().await?;
+    // The rejection has been reported once for both subscribers
+    alerts.interrupt()?;
+    alerts.join().await?;
+    assert!(rejected.try_recv().is_err());
+
+    // A rejected change of the common section is restored in the common section
+    let path = dir.join("agents.toml");
+    std::fs::write(
+        &path,
+        "[limits]\nmax = 1\n[agents.x]\nfactory = \"worker\"\n",
+    )?;
+    let (tx, mut rejected) = mpsc::unbounded_channel();
+    let mut alerts = Alerts { tx }.spawn();
+    let mut watcher = ConfigWatcher::new(&path).await?;
+    watcher.report_to(&alerts);
+    let mut address = watcher.spawn();
+    let agent_entry = address
+        .subscribe(ConfigSection::<Limits>::for_agent("x"))
+        .await?;
+    assert_eq!(agent_entry.state.max, 1);
+
+    std::fs::write(
+        &path,
+        "[limits]\nmax = 0\n[agents.x]\nfactory = \"worker\"\n",
+    )?;
+    let event = timeout(WAIT, rejected.recv()).await?.unwrap();
+    assert_eq!(event.agent.as_deref(), Some("x"));
+    let common = address.subscribe(ConfigSection::<Limits>::new()).await?;
+    assert_eq!(common.state.max, 1);
+
+    address.interrupt()?;
+    address.join().await?;
+    alerts.interrupt()?;
+    alerts.join().await?;
+    std::fs::remove_dir_all(&dir)?;
+    Ok(())
+}
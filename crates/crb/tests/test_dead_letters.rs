@@ -0,0 +1,80 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use crb::agent::{
+    Agent, AgentSession, Context, DeadLetter, DeadLetters, DeadReason, OnEvent, Standalone, CRB,
+};
+use crb::core::mpsc;
+
+struct Echo;
+
+impl Standalone for Echo {}
+
+impl Agent for Echo {
+    type Context = AgentSession<Self>;
+}
+
+struct Ping(u32);
+
+#[async_trait]
+impl OnEvent<Ping> for Echo {
+    async fn handle(&mut self, _event: Ping, _ctx: &mut Context<Self>) -> Result<()> {
+        Ok(())
+    }
+}
+
+struct Pong(u32);
+
+#[async_trait]
+impl OnEvent<Pong> for Echo {}
+
+struct Collector {
+    tx: mpsc::UnboundedSender<DeadLetter>,
+}
+
+impl Standalone for Collector {}
+
+impl Agent for Collector {
+    type Context = AgentSession<Self>;
+}
+
+#[async_trait]
+impl OnEvent<DeadLetter> for Collector {
+    async fn handle(&mut self, letter: DeadLetter, _ctx: &mut Context<Self>) -> Result<()> {
+        self.tx.send(letter).ok();
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn test_dead_letters() -> Result<()> {
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    let collector = Collector { tx }.spawn();
+    CRB.set_dead_letters(Some(collector.recipient()));
+
+    let mut echo = Echo.spawn();
+    echo.event(Pong(1))?;
+    let letter = rx.recv().await.unwrap();
+    assert_eq!(letter.reason, DeadReason::Unhandled);
+    assert!(letter.target.ends_with("Echo"));
+    assert_eq!(letter.event::<Pong>().map(|pong| pong.0), Some(1));
+
+    echo.interrupt()?;
+    echo.join().await?;
+    assert!(echo.event(Ping(2)).is_err());
+    let undeliverable = rx.recv().await.unwrap();
+    assert_eq!(undeliverable.reason, DeadReason::Undeliverable);
+    assert_eq!(undeliverable.event::<Ping>().map(|ping| ping.0), Some(2));
+
+    let dead_letters = DeadLetters::new();
+    let stats = dead_letters.stats();
+    let mut office = dead_letters.spawn();
+    office.event(letter)?;
+    office.event(undeliverable)?;
+    office.interrupt()?;
+    office.join().await?;
+    assert_eq!(stats.total(), 2);
+    assert_eq!(stats.count(DeadReason::Unhandled), 1);
+
+    CRB.set_dead_letters(None);
+    Ok(())
+}
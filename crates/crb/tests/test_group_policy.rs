@@ -0,0 +1,99 @@
+use anyhow::Result;
+use crb::agent::{Agent, AgentSession, Context, ManagedContext, Next, Standalone};
+use crb::core::time::{Duration, Instant};
+use crb::runtime::InterruptionLevel;
+use crb::superagent::{
+    GroupPolicy, Relation, Supervisor, SupervisorContext, SupervisorSession, Tracker,
+};
+use std::sync::{Arc, Mutex};
+
+type Log = Arc<Mutex<Vec<Layer>>>;
+
+// `Database` is greater and would be terminated first without dependencies
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+enum Layer {
+    Handlers,
+    Database,
+}
+
+struct Handler;
+
+impl Agent for Handler {
+    type Context = AgentSession<Self>;
+
+    // Ignores soft interruptions
+    fn interrupt(&mut self, _ctx: &mut Context<Self>) {}
+}
+
+struct Database;
+
+impl Agent for Database {
+    type Context = AgentSession<Self>;
+}
+
+struct App {
+    log: Log,
+}
+
+impl Standalone for App {}
+
+impl Supervisor for App {
+    type BasedOn = AgentSession<Self>;
+    type GroupBy = Layer;
+
+    fn finished(&mut self, rel: &Relation<Self>, ctx: &mut Context<Self>) {
+        let mut log = self.log.lock().unwrap();
+        log.push(rel.group);
+        let handlers = log
+            .iter()
+            .filter(|group| **group == Layer::Handlers)
+            .count();
+        if rel.group == Layer::Handlers && handlers == 2 {
+            // Reopens the closed group that must be terminated again
+            ctx.spawn_agent(Handler, Layer::Handlers);
+        }
+    }
+}
+
+impl Agent for App {
+    type Context = SupervisorSession<Self>;
+
+    fn initialize(&mut self, ctx: &mut Context<Self>) -> Next<Self> {
+        let policy = GroupPolicy::new()
+            .with_level(InterruptionLevel::EVENT)
+            .with_timeout(Duration::from_millis(50))
+            .not_restartable();
+        let tracker = ctx.tracker();
+        tracker.set_policy(Layer::Handlers, policy);
+        tracker
+            .add_dependency(Layer::Handlers, Layer::Database)
+            .expect("Groups don't make a cycle");
+        ctx.spawn_agent(Database, Layer::Database);
+        ctx.spawn_agent(Handler, Layer::Handlers);
+        ctx.spawn_agent(Handler, Layer::Handlers);
+        ctx.shutdown();
+        Next::events()
+    }
+}
+
+#[tokio::test]
+async fn test_group_policy() -> Result<()> {
+    let mut tracker = Tracker::<App>::new();
+    tracker.add_dependency(Layer::Handlers, Layer::Database)?;
+    assert!(tracker
+        .add_dependency(Layer::Database, Layer::Handlers)
+        .is_err());
+    assert!(tracker
+        .add_dependency(Layer::Handlers, Layer::Handlers)
+        .is_err());
+
+    let log = Log::default();
+    let started = Instant::now();
+    let mut app = App { log: log.clone() }.spawn();
+    app.join().await?;
+    assert!(started.elapsed() >= Duration::from_millis(100));
+    let log = log.lock().unwrap().clone();
+    use Layer::*;
+    assert_eq!(log, [Handlers, Handlers, Database, Handlers]);
+    Ok(())
+}
@@ -0,0 +1,65 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use crb::agent::{Agent, AgentSession, Context, OnEvent, Standalone};
+use crb::send::{Headers, Sender};
+use crb::superagent::{InteractExt, OnRequest, Request};
+
+#[derive(Default)]
+struct Tracer {
+    seen: Vec<Option<String>>,
+}
+
+impl Standalone for Tracer {}
+
+impl Agent for Tracer {
+    type Context = AgentSession<Self>;
+}
+
+struct Ping;
+
+#[async_trait]
+impl OnEvent<Ping> for Tracer {
+    async fn handle(&mut self, _event: Ping, ctx: &mut Context<Self>) -> Result<()> {
+        let correlation_id = ctx
+            .headers()
+            .and_then(|headers| headers.correlation_id.clone());
+        self.seen.push(correlation_id);
+        Ok(())
+    }
+}
+
+struct Report;
+
+type Trace = (Vec<Option<String>>, Option<String>);
+
+impl Request for Report {
+    type Response = Trace;
+}
+
+#[async_trait]
+impl OnRequest<Report> for Tracer {
+    async fn on_request(&mut self, _: Report, ctx: &mut Context<Self>) -> Result<Trace> {
+        let headers = ctx.headers().expect("headers are attached");
+        assert!(headers.timestamp.is_some());
+        let user = headers.get("user").map(String::from);
+        Ok((self.seen.clone(), user))
+    }
+}
+
+#[tokio::test]
+async fn test_headers() -> Result<()> {
+    let mut address = Tracer::default().spawn();
+    address.event(Ping)?;
+    address.event_with(Ping, Headers::new().with_correlation_id("first"))?;
+    let recipient = address.recipient::<Ping>();
+    recipient.send_with(Ping, Headers::new().with_correlation_id("second"))?;
+
+    let headers = Headers::new().with("user", "admin");
+    let (seen, user) = address.interact_with(Report, headers).await?;
+    assert_eq!(seen, [None, Some("first".into()), Some("second".into())]);
+    assert_eq!(user.as_deref(), Some("admin"));
+
+    address.interrupt()?;
+    address.join().await?;
+    Ok(())
+}
@@ -0,0 +1,128 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use crb::agent::{
+    Agent, AgentSession, Context, DeadLetter, DeadReason, DoAsync, MailboxPolicy, Next, OnEvent,
+    Standalone, CRB,
+};
+use crb::core::mpsc;
+use crb::core::time::{sleep, timeout, Duration};
+use crb::runtime::{InterruptionLevel, Interruptor};
+use crb::superagent::{InteractExt, OnRequest, Request};
+
+const TOTAL: usize = 20;
+
+struct Download {
+    policy: MailboxPolicy,
+    chunk_delay: Duration,
+    chunks: usize,
+}
+
+impl Standalone for Download {}
+
+impl Agent for Download {
+    type Context = AgentSession<Self>;
+
+    fn begin(&mut self) -> Next<Self> {
+        Next::do_hybrid_with(Downloading, self.policy)
+    }
+}
+
+struct Downloading;
+
+#[async_trait]
+impl DoAsync<Downloading> for Download {
+    async fn repeat(&mut self, _: &mut Downloading) -> Result<Option<Next<Self>>> {
+        sleep(self.chunk_delay).await;
+        self.chunks += 1;
+        if self.chunks < TOTAL {
+            Ok(None)
+        } else {
+            Ok(Some(Next::events()))
+        }
+    }
+}
+
+struct Status;
+
+impl Request for Status {
+    type Response = usize;
+}
+
+#[async_trait]
+impl OnRequest<Status> for Download {
+    async fn on_request(&mut self, _: Status, _ctx: &mut Context<Self>) -> Result<usize> {
+        Ok(self.chunks)
+    }
+}
+
+struct Collector {
+    tx: mpsc::UnboundedSender<DeadLetter>,
+}
+
+impl Standalone for Collector {}
+
+impl Agent for Collector {
+    type Context = AgentSession<Self>;
+}
+
+#[async_trait]
+impl OnEvent<DeadLetter> for Collector {
+    async fn handle(&mut self, letter: DeadLetter, _ctx: &mut Context<Self>) -> Result<()> {
+        self.tx.send(letter).ok();
+        Ok(())
+    }
+}
+
+async fn status_with(policy: MailboxPolicy, chunk_delay: Duration) -> Result<usize> {
+    let download = Download {
+        policy,
+        chunk_delay,
+        chunks: 0,
+    };
+    let mut address = download.spawn();
+    sleep(Duration::from_millis(20)).await;
+    let status = address.interact(Status).await;
+    address.interrupt()?;
+    address.join().await?;
+    Ok(status?)
+}
+
+#[tokio::test]
+async fn test_hybrid_state() -> Result<()> {
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    let collector = Collector { tx }.spawn();
+    CRB.set_dead_letters(Some(collector.recipient()));
+
+    let short = Duration::from_millis(5);
+    let chunks = status_with(MailboxPolicy::Handle, short).await?;
+    assert!(chunks > 0 && chunks < TOTAL);
+
+    // A long iteration doesn't block the handling
+    let download = Download {
+        policy: MailboxPolicy::Handle,
+        chunk_delay: Duration::from_secs(60),
+        chunks: 0,
+    };
+    let mut address = download.spawn();
+    let chunks = timeout(Duration::from_secs(5), address.interact(Status)).await??;
+    assert_eq!(chunks, 0);
+    address.interrupt_with_level(InterruptionLevel::ABORT);
+    address.join().await?;
+
+    let chunks = status_with(MailboxPolicy::Defer, short).await?;
+    assert_eq!(chunks, TOTAL);
+
+    // The request is canceled when the dead letter is dropped
+    let rejected = async {
+        let letter = rx.recv().await.unwrap();
+        assert_eq!(letter.reason, DeadReason::Rejected);
+        assert!(letter
+            .message
+            .ends_with("Interaction<test_hybrid_state::Status>"));
+    };
+    let (status, ()) = futures::join!(status_with(MailboxPolicy::Reject, short), rejected);
+    assert!(status.is_err());
+
+    CRB.set_dead_letters(None);
+    Ok(())
+}
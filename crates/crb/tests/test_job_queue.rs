@@ -0,0 +1,107 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use crb::agent::{Agent, AgentContext, AgentSession, Context, DoAsync, Next, Standalone};
+use crb::core::time::{sleep, Duration};
+use crb::superagent::{Job, JobQueue, JobQueueExt, Mission, RunMission};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+#[derive(Default)]
+struct Stats {
+    active: AtomicUsize,
+    peak: AtomicUsize,
+    order: Mutex<Vec<u64>>,
+}
+
+fn work(stats: &Arc<Stats>, value: u64) -> RunMission<impl Mission<Goal = u64>> {
+    let stats = stats.clone();
+    RunMission::new_async(async move {
+        let active = stats.active.fetch_add(1, Ordering::SeqCst) + 1;
+        stats.peak.fetch_max(active, Ordering::SeqCst);
+        stats.order.lock().unwrap().push(value);
+        sleep(Duration::from_millis(10)).await;
+        stats.active.fetch_sub(1, Ordering::SeqCst);
+        value
+    })
+}
+
+// Never finishes, but delivers a partial goal when canceled
+struct Endless;
+
+impl Agent for Endless {
+    type Context = AgentSession<Self>;
+
+    fn begin(&mut self) -> Next<Self> {
+        Next::do_async(())
+    }
+}
+
+#[async_trait]
+impl DoAsync for Endless {
+    async fn handle(&mut self, _: (), ctx: &mut Context<Self>) -> Result<Next<Self>> {
+        let stopper = ctx.session().controller.stopper.clone();
+        while stopper.is_active() {
+            sleep(Duration::from_millis(1)).await;
+        }
+        Ok(Next::done())
+    }
+}
+
+#[async_trait]
+impl Mission for Endless {
+    type Goal = u64;
+
+    async fn deliver(self, _ctx: &mut Context<Self>) -> Option<u64> {
+        None
+    }
+
+    async fn deliver_partial(self, _ctx: &mut Context<Self>) -> Option<u64> {
+        Some(0)
+    }
+}
+
+#[tokio::test]
+async fn test_job_queue() -> Result<()> {
+    let stats = Arc::new(Stats::default());
+    let mut queue = JobQueue::new(2).spawn();
+
+    let first = queue.submit(work(&stats, 1));
+    let second = queue.submit(work(&stats, 2));
+    let low = queue.submit(work(&stats, 3));
+    let high = queue.submit(Job::new(work(&stats, 4)).with_priority(10));
+    let canceled = Job::new(work(&stats, 5));
+    let id = canceled.id();
+    let canceled = queue.submit(canceled);
+    queue.cancel_job(id)?;
+
+    assert_eq!(first.await?, 1);
+    assert_eq!(second.await?, 2);
+    assert_eq!(high.await?, 4);
+    assert_eq!(low.await?, 3);
+    assert!(canceled.await.is_err());
+
+    assert_eq!(stats.peak.load(Ordering::SeqCst), 2);
+    assert_eq!(*stats.order.lock().unwrap(), vec![1, 2, 4, 3]);
+
+    queue.interrupt()?;
+    queue.join().await?;
+
+    let mut endless_queue = JobQueue::new(0).spawn();
+    let endless = Job::new(RunMission::new(Endless));
+    let id = endless.id();
+    let endless = endless_queue.submit(endless);
+    endless_queue.cancel_job(id)?;
+    assert!(endless.await.is_err());
+    endless_queue.interrupt()?;
+    endless_queue.join().await?;
+
+    // Running and queued jobs fail when the queue is terminated
+    let mut endless_queue = JobQueue::new(1).spawn();
+    let running = endless_queue.submit(RunMission::new(Endless));
+    let queued = endless_queue.submit(RunMission::new(Endless));
+    endless_queue.interrupt()?;
+    assert!(running.await.is_err());
+    assert!(queued.await.is_err());
+    endless_queue.join().await?;
+    Ok(())
+}
@@ -0,0 +1,124 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use crb::agent::{Agent, AgentSession, Context, DeadLetter, DeadReason, OnEvent, Standalone, CRB};
+use crb::core::{mpsc, time::sleep, time::Duration};
+use crb::send::Headers;
+use crb::superagent::{InteractExt, OnRequest, Request};
+
+#[derive(Default)]
+struct Sensor {
+    readings: Vec<u32>,
+}
+
+impl Standalone for Sensor {}
+
+impl Agent for Sensor {
+    type Context = AgentSession<Self>;
+}
+
+struct Busy;
+
+#[async_trait]
+impl OnEvent<Busy> for Sensor {
+    async fn handle(&mut self, _event: Busy, _ctx: &mut Context<Self>) -> Result<()> {
+        sleep(Duration::from_millis(100)).await;
+        Ok(())
+    }
+}
+
+/// Takes the next reading out of order.
+struct Calibrate;
+
+#[async_trait]
+impl OnEvent<Calibrate> for Sensor {
+    async fn handle(&mut self, _event: Calibrate, ctx: &mut Context<Self>) -> Result<()> {
+        sleep(Duration::from_millis(100)).await;
+        if let Some(reading) = ctx.receive::<Reading>().await {
+            self.readings.push(reading.0);
+        }
+        Ok(())
+    }
+}
+
+struct Reading(u32);
+
+#[async_trait]
+impl OnEvent<Reading> for Sensor {
+    async fn handle(&mut self, event: Reading, _ctx: &mut Context<Self>) -> Result<()> {
+        self.readings.push(event.0);
+        Ok(())
+    }
+}
+
+struct Stats;
+
+impl Request for Stats {
+    type Response = (Vec<u32>, usize);
+}
+
+#[async_trait]
+impl OnRequest<Stats> for Sensor {
+    async fn on_request(&mut self, _: Stats, ctx: &mut Context<Self>) -> Result<(Vec<u32>, usize)> {
+        Ok((self.readings.clone(), ctx.expired_messages()))
+    }
+}
+
+struct Collector {
+    tx: mpsc::UnboundedSender<DeadLetter>,
+}
+
+impl Standalone for Collector {}
+
+impl Agent for Collector {
+    type Context = AgentSession<Self>;
+}
+
+#[async_trait]
+impl OnEvent<DeadLetter> for Collector {
+    async fn handle(&mut self, letter: DeadLetter, _ctx: &mut Context<Self>) -> Result<()> {
+        self.tx.send(letter).ok();
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn test_message_deadline() -> Result<()> {
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    let collector = Collector { tx }.spawn();
+    CRB.set_dead_letters(Some(collector.recipient()));
+
+    let mut address = Sensor::default().spawn();
+    address.event(Busy)?;
+    let short = Headers::new().with_ttl(Duration::from_millis(10));
+    address.event_with(Reading(1), short)?;
+    let long = Headers::new().with_ttl(Duration::from_secs(10));
+    address.event_with(Reading(2), long)?;
+
+    let (readings, expired) = address.interact(Stats).await?;
+    assert_eq!(readings, [2]);
+    assert_eq!(expired, 1);
+
+    let letter = rx.recv().await.unwrap();
+    assert_eq!(letter.reason, DeadReason::Expired);
+    assert_eq!(letter.event::<Reading>().map(|reading| reading.0), Some(1));
+
+    // Received messages are checked too
+    address.event(Calibrate)?;
+    let short = Headers::new().with_ttl(Duration::from_millis(10));
+    address.event_with(Reading(3), short)?;
+    let long = Headers::new().with_ttl(Duration::from_secs(10));
+    address.event_with(Reading(4), long)?;
+
+    let (readings, expired) = address.interact(Stats).await?;
+    assert_eq!(readings, [2, 4]);
+    assert_eq!(expired, 2);
+
+    let letter = rx.recv().await.unwrap();
+    assert_eq!(letter.reason, DeadReason::Expired);
+    assert_eq!(letter.event::<Reading>().map(|reading| reading.0), Some(3));
+
+    address.interrupt()?;
+    address.join().await?;
+    CRB.set_dead_letters(None);
+    Ok(())
+}
@@ -0,0 +1,99 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use crb::agent::{Agent, AgentContext, AgentSession, Context, DoAsync, Next, OnEvent, Standalone};
+use crb::core::time::{sleep, Duration};
+use crb::runtime::InterruptionLevel;
+use crb::superagent::{CancelHandle, Mission, Progress, ProgressExt, RunMission};
+
+struct Import {
+    total: usize,
+    imported: Vec<usize>,
+}
+
+struct Report {
+    imported: Vec<usize>,
+    complete: bool,
+}
+
+impl Agent for Import {
+    type Context = AgentSession<Self>;
+
+    fn begin(&mut self) -> Next<Self> {
+        Next::do_async(Importing)
+    }
+}
+
+struct Importing;
+
+#[async_trait]
+impl DoAsync<Importing> for Import {
+    async fn handle(&mut self, _: Importing, ctx: &mut Context<Self>) -> Result<Next<Self>> {
+        let stopper = ctx.session().controller.stopper.clone();
+        while stopper.is_active() && self.imported.len() < self.total {
+            sleep(Duration::from_millis(1)).await;
+            self.imported.push(self.imported.len());
+            ctx.progress(self.imported.len());
+        }
+        Ok(Next::done())
+    }
+}
+
+#[async_trait]
+impl Mission for Import {
+    type Goal = Report;
+
+    async fn deliver(self, _ctx: &mut Context<Self>) -> Option<Self::Goal> {
+        Some(Report {
+            imported: self.imported,
+            complete: true,
+        })
+    }
+
+    async fn deliver_partial(self, _ctx: &mut Context<Self>) -> Option<Self::Goal> {
+        Some(Report {
+            imported: self.imported,
+            complete: false,
+        })
+    }
+}
+
+struct Watcher {
+    cancel: CancelHandle<Import>,
+}
+
+impl Standalone for Watcher {}
+
+impl Agent for Watcher {
+    type Context = AgentSession<Self>;
+}
+
+#[async_trait]
+impl OnEvent<Progress<usize>> for Watcher {
+    async fn handle(&mut self, event: Progress<usize>, _ctx: &mut Context<Self>) -> Result<()> {
+        if event.value == 3 {
+            self.cancel.cancel("Enough");
+        }
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn test_mission_cancel() -> Result<()> {
+    let import = Import {
+        total: 1_000,
+        imported: Vec::new(),
+    };
+    let mut mission = RunMission::new(import);
+    let cancel = mission.cancel_handle();
+    let watcher = Watcher {
+        cancel: cancel.clone(),
+    }
+    .spawn();
+    mission.report_progress_to::<usize, _>(&watcher);
+    let report = mission.await?;
+    assert!(!report.complete);
+    assert!(report.imported.len() >= 3);
+    assert_eq!(cancel.reason().as_deref(), Some("Enough"));
+    assert_eq!(cancel.level(), Some(InterruptionLevel::FLAG));
+    Ok(())
+}
@@ -0,0 +1,66 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use crb::agent::{Agent, AgentSession, Context, Next};
+use crb::core::time::{sleep, Duration};
+use crb::superagent::{
+    join_all, race, retry, sequence, timeout, Mission, MissionError, RetryPolicy, RunMission,
+};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+fn delayed(value: u64) -> RunMission<impl Mission<Goal = u64>> {
+    RunMission::new_async(async move {
+        sleep(Duration::from_millis(value)).await;
+        value
+    })
+}
+
+#[tokio::test]
+async fn test_mission_combinators() -> Result<()> {
+    let goals = join_all([delayed(30), delayed(10), delayed(20)]).await?;
+    assert_eq!(goals, vec![30, 10, 20]);
+
+    let goal = race([delayed(1_000), delayed(10)]).await?;
+    assert_eq!(goal, 10);
+
+    let goals = sequence([delayed(2), delayed(1)]).await?;
+    assert_eq!(goals, vec![2, 1]);
+
+    let goal = delayed(5).and_then(|value| delayed(value * 2)).await?;
+    assert_eq!(goal, 10);
+
+    let timed_out = timeout(Duration::from_millis(10), delayed(1_000)).await;
+    assert!(matches!(timed_out, Err(MissionError::TimedOut)));
+
+    let attempts = Arc::new(AtomicUsize::new(0));
+    let policy = RetryPolicy::attempts(3).with_delay(Duration::from_millis(1));
+    let counter = attempts.clone();
+    let goal = retry(policy, move || {
+        let attempt = counter.fetch_add(1, Ordering::SeqCst) + 1;
+        RunMission::new(Flaky { attempt })
+    });
+    assert_eq!(goal.await?, 3);
+    assert_eq!(attempts.load(Ordering::SeqCst), 3);
+    Ok(())
+}
+
+struct Flaky {
+    attempt: usize,
+}
+
+impl Agent for Flaky {
+    type Context = AgentSession<Self>;
+
+    fn begin(&mut self) -> Next<Self> {
+        Next::done()
+    }
+}
+
+#[async_trait]
+impl Mission for Flaky {
+    type Goal = usize;
+
+    async fn deliver(self, _ctx: &mut Context<Self>) -> Option<Self::Goal> {
+        Some(self.attempt).filter(|attempt| *attempt >= 3)
+    }
+}
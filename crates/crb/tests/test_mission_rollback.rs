@@ -0,0 +1,57 @@
+use anyhow::{Error, Result};
+use async_trait::async_trait;
+use crb::agent::{Agent, AgentSession, Context, DoSync, Next};
+use crb::superagent::{Mission, MissionError, RunMission};
+use std::sync::atomic::{AtomicBool, Ordering};
+use tokio::task::JoinError;
+
+static PANIC_ROLLED_BACK: AtomicBool = AtomicBool::new(false);
+
+struct Migration;
+
+#[async_trait]
+impl Agent for Migration {
+    type Context = AgentSession<Self>;
+
+    fn begin(&mut self) -> Next<Self> {
+        Next::do_sync(Migrating)
+    }
+
+    async fn rollback(_this: Option<&mut Self>, err: Error, _ctx: &mut Context<Self>) {
+        // The rollback gets the original error
+        let panicked = err
+            .downcast_ref::<JoinError>()
+            .is_some_and(JoinError::is_panic);
+        PANIC_ROLLED_BACK.store(panicked, Ordering::SeqCst);
+    }
+}
+
+struct Migrating;
+
+impl DoSync<Migrating> for Migration {
+    fn once(&mut self, _: &mut Migrating) -> Result<Next<Self>> {
+        panic!("The schema is broken");
+    }
+}
+
+#[async_trait]
+impl Mission for Migration {
+    type Goal = ();
+
+    async fn deliver(self, _ctx: &mut Context<Self>) -> Option<Self::Goal> {
+        Some(())
+    }
+}
+
+#[tokio::test]
+async fn test_mission_rollback() -> Result<()> {
+    let result = RunMission::new(Migration).await;
+    match result {
+        Err(MissionError::Crashed(err)) => {
+            assert!(format!("{err:#}").contains("panic"));
+        }
+        _ => panic!("The mission must crash"),
+    }
+    assert!(PANIC_ROLLED_BACK.load(Ordering::SeqCst));
+    Ok(())
+}
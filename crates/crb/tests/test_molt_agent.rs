@@ -0,0 +1,67 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use crb::agent::{Agent, AgentSession, Context, ManagedContext, Next, OnEvent, Standalone};
+use crb::superagent::{
+    MoltAgent, MoltTo, Molted, MoltingSession, NextExt, Supervisor, SupervisorSession,
+};
+
+struct Parent;
+
+impl Standalone for Parent {}
+
+impl Supervisor for Parent {
+    type BasedOn = AgentSession<Self>;
+    type GroupBy = ();
+}
+
+impl Agent for Parent {
+    type Context = SupervisorSession<Self>;
+
+    fn initialize(&mut self, ctx: &mut Context<Self>) -> Next<Self> {
+        let mut agent = MoltAgent::new(Egg);
+        agent.report_to(&*ctx);
+        let (address, _) = ctx.spawn_runtime(agent, ());
+        assert!(address.form().is_some());
+        Next::events()
+    }
+}
+
+#[async_trait]
+impl OnEvent<Molted> for Parent {
+    async fn handle(&mut self, event: Molted, ctx: &mut Context<Self>) -> Result<()> {
+        assert!(event.from.ends_with("Egg"));
+        assert!(event.to.ends_with("Larva"));
+        // Interrupts the current form of the molting agent
+        ctx.shutdown();
+        Ok(())
+    }
+}
+
+struct Egg;
+
+impl Agent for Egg {
+    type Context = MoltingSession<Self>;
+
+    fn begin(&mut self) -> Next<Self> {
+        Next::molt::<Larva>()
+    }
+}
+
+impl MoltTo<Larva> for Egg {
+    fn molt(self) -> Option<Larva> {
+        Some(Larva)
+    }
+}
+
+struct Larva;
+
+impl Agent for Larva {
+    type Context = MoltingSession<Self>;
+}
+
+#[tokio::test]
+async fn test_molt_agent() -> Result<()> {
+    let mut addr = Parent.spawn();
+    addr.join().await?;
+    Ok(())
+}
@@ -0,0 +1,74 @@
+use anyhow::{anyhow, Error, Result};
+use async_trait::async_trait;
+use crb::agent::{Agent, Context, Next, Task};
+use crb::superagent::{MoltAgent, MoltAsync, MoltError, MoltingSession, NextExt, NextForm};
+use std::sync::{Arc, Mutex};
+
+type Journal = Arc<Mutex<Vec<&'static str>>>;
+
+struct Connection {
+    token: Option<&'static str>,
+    journal: Journal,
+}
+
+impl Agent for Connection {
+    type Context = MoltingSession<Self>;
+
+    fn begin(&mut self) -> Next<Self> {
+        Next::molt_async()
+    }
+
+    fn failed(&mut self, _err: Error, _ctx: &mut Context<Self>) {
+        self.journal.lock().unwrap().push("failed");
+    }
+}
+
+#[async_trait]
+impl MoltAsync for Connection {
+    async fn molt_async(self, _ctx: &mut Context<Self>) -> Result<NextForm, MoltError<Self>> {
+        let journal = self.journal.clone();
+        match self.token {
+            Some("secret") => Ok(NextForm::new(Authenticated { journal })),
+            Some(_) => Ok(NextForm::new(Rejected { journal })),
+            None => Err(MoltError::recover(self, anyhow!("No token"))),
+        }
+    }
+}
+
+struct Authenticated {
+    journal: Journal,
+}
+
+impl Agent for Authenticated {
+    type Context = MoltingSession<Self>;
+
+    fn begin(&mut self) -> Next<Self> {
+        self.journal.lock().unwrap().push("authenticated");
+        Next::done()
+    }
+}
+
+struct Rejected {
+    journal: Journal,
+}
+
+impl Agent for Rejected {
+    type Context = MoltingSession<Self>;
+
+    fn begin(&mut self) -> Next<Self> {
+        self.journal.lock().unwrap().push("rejected");
+        Next::done()
+    }
+}
+
+#[tokio::test]
+async fn test_molt_async() -> Result<()> {
+    let journal = Journal::default();
+    for token in [Some("secret"), Some("guess"), None] {
+        let journal = journal.clone();
+        MoltAgent::new(Connection { token, journal }).run().await;
+    }
+    let journal = journal.lock().unwrap();
+    assert_eq!(*journal, vec!["authenticated", "rejected", "failed"]);
+    Ok(())
+}
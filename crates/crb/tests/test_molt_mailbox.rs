@@ -0,0 +1,117 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use crb::agent::{
+    Agent, AgentContext, AgentSession, Context, DeadLetter, DeadReason, Envelope, Event,
+    ManagedContext, Next, OnEvent, Standalone, Task, CRB,
+};
+use crb::core::mpsc;
+use crb::core::time::{timeout, Duration};
+use crb::superagent::{Form, MoltAgent, MoltTo, MoltingSession, NextExt};
+
+struct Grow;
+
+struct Note(u32);
+
+struct Larva;
+
+impl Agent for Larva {
+    type Context = MoltingSession<Self, Grow>;
+}
+
+impl Form<Grow> for Larva {
+    fn envelope(msg: Grow) -> Envelope<Self> {
+        Event::envelope(msg)
+    }
+}
+
+#[async_trait]
+impl OnEvent<Grow> for Larva {
+    async fn handle(&mut self, msg: Grow, ctx: &mut Context<Self>) -> Result<()> {
+        // Messages of the protocol are forwarded to the next form
+        ctx.stash_event(msg)?;
+        // Left in the own mailbox of the form
+        ctx.event(Note(1))?;
+        ctx.event(Note(2))?;
+        ctx.session().do_next(Next::molt::<Butterfly>());
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl OnEvent<Note> for Larva {
+    async fn handle(&mut self, _msg: Note, _ctx: &mut Context<Self>) -> Result<()> {
+        panic!("The form has molted");
+    }
+}
+
+impl MoltTo<Butterfly> for Larva {
+    fn molt(self) -> Option<Butterfly> {
+        Some(Butterfly { grown: 0 })
+    }
+}
+
+struct Butterfly {
+    grown: usize,
+}
+
+impl Agent for Butterfly {
+    type Context = MoltingSession<Self, Grow>;
+}
+
+impl Form<Grow> for Butterfly {
+    fn envelope(msg: Grow) -> Envelope<Self> {
+        Event::envelope(msg)
+    }
+}
+
+#[async_trait]
+impl OnEvent<Grow> for Butterfly {
+    async fn handle(&mut self, _msg: Grow, ctx: &mut Context<Self>) -> Result<()> {
+        self.grown += 1;
+        if self.grown == 2 {
+            ctx.shutdown();
+        }
+        Ok(())
+    }
+}
+
+struct Collector {
+    tx: mpsc::UnboundedSender<DeadLetter>,
+}
+
+impl Standalone for Collector {}
+
+impl Agent for Collector {
+    type Context = AgentSession<Self>;
+}
+
+#[async_trait]
+impl OnEvent<DeadLetter> for Collector {
+    async fn handle(&mut self, letter: DeadLetter, _ctx: &mut Context<Self>) -> Result<()> {
+        self.tx.send(letter).ok();
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn test_molt_mailbox() -> Result<()> {
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    let collector = Collector { tx }.spawn();
+    CRB.set_dead_letters(Some(collector.recipient()));
+
+    let agent = MoltAgent::new(Larva);
+    let address = agent.molt_address();
+    address.send(Grow)?;
+    address.send(Grow)?;
+    timeout(Duration::from_secs(5), agent.run()).await?;
+
+    for expected in [1, 2] {
+        let letter = rx.recv().await.unwrap();
+        assert_eq!(letter.reason, DeadReason::Undeliverable);
+        assert!(letter.target.ends_with("Larva"));
+        assert_eq!(letter.event::<Note>().map(|note| note.0), Some(expected));
+    }
+
+    CRB.set_dead_letters(None);
+    Ok(())
+}
@@ -0,0 +1,78 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use crb::agent::{
+    Agent, AgentContext, Context, Envelope, Event, ManagedContext, Next, OnEvent, Task,
+};
+use crb::superagent::{Form, MoltAgent, MoltTo, MoltingSession, NextExt};
+
+enum Protocol {
+    Hello,
+    Bye,
+}
+
+struct Larva;
+
+impl Agent for Larva {
+    type Context = MoltingSession<Self, Protocol>;
+}
+
+impl Form<Protocol> for Larva {
+    fn envelope(msg: Protocol) -> Envelope<Self> {
+        Event::envelope(msg)
+    }
+}
+
+#[async_trait]
+impl OnEvent<Protocol> for Larva {
+    async fn handle(&mut self, _msg: Protocol, ctx: &mut Context<Self>) -> Result<()> {
+        ctx.session().do_next(Next::molt::<Butterfly>());
+        Ok(())
+    }
+}
+
+impl MoltTo<Butterfly> for Larva {
+    fn molt(self) -> Option<Butterfly> {
+        Some(Butterfly { greeted: 0 })
+    }
+}
+
+struct Butterfly {
+    greeted: usize,
+}
+
+impl Agent for Butterfly {
+    type Context = MoltingSession<Self, Protocol>;
+}
+
+impl Form<Protocol> for Butterfly {
+    fn envelope(msg: Protocol) -> Envelope<Self> {
+        Event::envelope(msg)
+    }
+}
+
+#[async_trait]
+impl OnEvent<Protocol> for Butterfly {
+    async fn handle(&mut self, msg: Protocol, ctx: &mut Context<Self>) -> Result<()> {
+        match msg {
+            Protocol::Hello => {
+                self.greeted += 1;
+            }
+            Protocol::Bye => {
+                assert_eq!(self.greeted, 1);
+                ctx.shutdown();
+            }
+        }
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn test_molt_protocol() -> Result<()> {
+    let agent = MoltAgent::new(Larva);
+    let address = agent.molt_address();
+    address.send(Protocol::Hello)?;
+    address.send(Protocol::Hello)?;
+    address.send(Protocol::Bye)?;
+    agent.run().await;
+    Ok(())
+}
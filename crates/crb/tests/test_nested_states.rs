@@ -0,0 +1,178 @@
+use anyhow::{anyhow, Error, Result};
+use async_trait::async_trait;
+use crb::agent::{Agent, AgentSession, Context, DoAsync, NestedState, Next, Standalone, TopState};
+use std::sync::{Arc, Mutex};
+
+type Journal = Arc<Mutex<Vec<String>>>;
+
+struct Protocol {
+    journal: Journal,
+}
+
+impl Protocol {
+    fn log(&self, record: &str) {
+        self.journal.lock().unwrap().push(record.into());
+    }
+}
+
+impl Standalone for Protocol {}
+
+impl Agent for Protocol {
+    type Context = AgentSession<Self>;
+
+    fn begin(&mut self) -> Next<Self> {
+        Next::enter(Connecting)
+    }
+}
+
+struct Online;
+
+impl NestedState<Protocol> for Online {
+    type Parent = TopState;
+
+    fn on_enter(agent: &mut Protocol, _ctx: &mut Context<Protocol>) {
+        agent.log("enter Online");
+    }
+
+    fn on_exit(agent: &mut Protocol, _ctx: &mut Context<Protocol>) {
+        agent.log("exit Online");
+    }
+
+    fn on_error(
+        agent: &mut Protocol,
+        err: Error,
+        _ctx: &mut Context<Protocol>,
+    ) -> Result<Next<Protocol>> {
+        agent.log(&format!("recover {err}"));
+        Ok(Next::enter(Ready))
+    }
+}
+
+struct Connecting;
+
+impl NestedState<Protocol> for Connecting {
+    type Parent = Online;
+
+    fn on_enter(agent: &mut Protocol, _ctx: &mut Context<Protocol>) {
+        agent.log("enter Connecting");
+    }
+
+    fn on_exit(agent: &mut Protocol, _ctx: &mut Context<Protocol>) {
+        agent.log("exit Connecting");
+    }
+}
+
+#[async_trait]
+impl DoAsync<Connecting> for Protocol {
+    async fn once(&mut self, _: &mut Connecting) -> Result<Next<Self>> {
+        Err(anyhow!("timeout"))
+    }
+}
+
+struct Ready;
+
+impl NestedState<Protocol> for Ready {
+    type Parent = Online;
+
+    fn on_enter(agent: &mut Protocol, _ctx: &mut Context<Protocol>) {
+        agent.log("enter Ready");
+    }
+
+    fn on_exit(agent: &mut Protocol, _ctx: &mut Context<Protocol>) {
+        agent.log("exit Ready");
+    }
+}
+
+#[async_trait]
+impl DoAsync<Ready> for Protocol {
+    async fn handle(&mut self, _: Ready, ctx: &mut Context<Self>) -> Result<Next<Self>> {
+        assert!(ctx.states().is_in::<Online>());
+        assert!(!ctx.states().is_in::<Connecting>());
+        Ok(Next::enter(Offline))
+    }
+}
+
+struct Offline;
+
+impl NestedState<Protocol> for Offline {
+    type Parent = TopState;
+
+    fn on_enter(agent: &mut Protocol, _ctx: &mut Context<Protocol>) {
+        agent.log("enter Offline");
+    }
+
+    fn on_exit(agent: &mut Protocol, _ctx: &mut Context<Protocol>) {
+        agent.log("exit Offline");
+    }
+}
+
+#[async_trait]
+impl DoAsync<Offline> for Protocol {
+    async fn handle(&mut self, _: Offline, ctx: &mut Context<Self>) -> Result<Next<Self>> {
+        let history: Vec<_> = ctx.states().history().map(|record| record.to).collect();
+        assert_eq!(history.len(), 3);
+        assert!(history[2].is_some_and(|to| to.ends_with("Offline")));
+        Ok(Next::do_async(Idle))
+    }
+}
+
+struct Idle;
+
+#[async_trait]
+impl DoAsync<Idle> for Protocol {
+    async fn handle(&mut self, _: Idle, ctx: &mut Context<Self>) -> Result<Next<Self>> {
+        assert!(ctx.states().active().is_empty());
+        // Leaving to a state that is not nested is recorded too
+        let last = ctx.states().history().last().cloned().unwrap();
+        assert!(last.from.is_some_and(|from| from.ends_with("Offline")));
+        assert_eq!(last.to, None);
+        self.log("idle");
+        Ok(Next::enter(Looped))
+    }
+}
+
+// Refers to itself as a parent
+struct Looped;
+
+impl NestedState<Protocol> for Looped {
+    type Parent = Looped;
+
+    fn on_enter(agent: &mut Protocol, _ctx: &mut Context<Protocol>) {
+        agent.log("enter Looped");
+    }
+}
+
+#[async_trait]
+impl DoAsync<Looped> for Protocol {
+    async fn handle(&mut self, _: Looped, ctx: &mut Context<Self>) -> Result<Next<Self>> {
+        assert_eq!(ctx.states().active().len(), 1);
+        Ok(Next::done())
+    }
+}
+
+#[tokio::test]
+async fn test_nested_states() -> Result<()> {
+    let journal = Journal::default();
+    let protocol = Protocol {
+        journal: journal.clone(),
+    };
+    let mut address = protocol.spawn();
+    address.join().await?;
+    assert_eq!(
+        *journal.lock().unwrap(),
+        vec![
+            "enter Online",
+            "enter Connecting",
+            "recover timeout",
+            "exit Connecting",
+            "enter Ready",
+            "exit Ready",
+            "exit Online",
+            "enter Offline",
+            "exit Offline",
+            "idle",
+            "enter Looped",
+        ]
+    );
+    Ok(())
+}
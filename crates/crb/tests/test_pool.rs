@@ -0,0 +1,162 @@
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use crb::agent::{
+    Agent, AgentSession, Backoff, Context, ManagedContext, Next, OnEvent, Standalone,
+};
+use crb::core::mpsc;
+use crb::core::time::{sleep, Duration};
+use crb::send::Headers;
+use crb::superagent::{InteractExt, OnRequest, Pool, PoolExt, Request, Routing};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+struct Worker {
+    id: usize,
+}
+
+impl Agent for Worker {
+    type Context = AgentSession<Self>;
+}
+
+struct WhoAmI;
+
+impl Request for WhoAmI {
+    type Response = usize;
+}
+
+#[async_trait]
+impl OnRequest<WhoAmI> for Worker {
+    async fn on_request(&mut self, _: WhoAmI, _ctx: &mut Context<Self>) -> Result<usize> {
+        Ok(self.id)
+    }
+}
+
+struct Correlation;
+
+impl Request for Correlation {
+    type Response = Option<String>;
+}
+
+#[async_trait]
+impl OnRequest<Correlation> for Worker {
+    async fn on_request(
+        &mut self,
+        _: Correlation,
+        ctx: &mut Context<Self>,
+    ) -> Result<Option<String>> {
+        let headers = ctx.headers();
+        Ok(headers.and_then(|headers| headers.correlation_id.clone()))
+    }
+}
+
+struct Crash;
+
+#[async_trait]
+impl OnEvent<Crash> for Worker {
+    async fn handle(&mut self, _: Crash, ctx: &mut Context<Self>) -> Result<()> {
+        ctx.shutdown();
+        Ok(())
+    }
+}
+
+/// Keeps the worker busy.
+struct Hold;
+
+#[async_trait]
+impl OnEvent<Hold> for Worker {
+    async fn handle(&mut self, _: Hold, _ctx: &mut Context<Self>) -> Result<()> {
+        sleep(Duration::from_millis(300)).await;
+        Ok(())
+    }
+}
+
+/// Fails on every start.
+struct Broken;
+
+impl Agent for Broken {
+    type Context = AgentSession<Self>;
+
+    fn initialize(&mut self, _ctx: &mut Context<Self>) -> Next<Self> {
+        Next::fail(anyhow!("Can't start"))
+    }
+}
+
+#[tokio::test]
+async fn test_pool() -> Result<()> {
+    let counter = Arc::new(AtomicUsize::new(0));
+    let ids = counter.clone();
+    let (spawned_tx, mut spawned) = mpsc::unbounded_channel();
+    let mut pool = Pool::new(3, move || {
+        let id = ids.fetch_add(1, Ordering::SeqCst);
+        spawned_tx.send(id).ok();
+        Worker { id }
+    })
+    .spawn();
+
+    let mut served = Vec::new();
+    for _ in 0..6 {
+        served.push(pool.interact(WhoAmI).await?);
+    }
+    assert_eq!(served, vec![0, 1, 2, 0, 1, 2]);
+
+    let headers = Headers::new().with_correlation_id("job");
+    let correlation_id = pool.interact_with(Correlation, headers).await?;
+    assert_eq!(correlation_id.as_deref(), Some("job"));
+
+    let owner = pool.interact_by_key("key", WhoAmI).await?;
+    assert_eq!(pool.interact_by_key("key", WhoAmI).await?, owner);
+
+    for id in 0..3 {
+        assert_eq!(spawned.recv().await, Some(id));
+    }
+    pool.event_by_key("key", Crash)?;
+    // The replacement of the crashed worker
+    assert_eq!(spawned.recv().await, Some(3));
+    assert_eq!(pool.interact_by_key("key", WhoAmI).await?, 3);
+
+    pool.resize(1)?;
+    let single = pool.interact(WhoAmI).await?;
+    for _ in 0..3 {
+        assert_eq!(pool.interact(WhoAmI).await?, single);
+    }
+
+    pool.route(Crash)?;
+    assert_eq!(spawned.recv().await, Some(4));
+    assert_eq!(pool.interact(WhoAmI).await?, 4);
+
+    pool.interrupt()?;
+    pool.join().await?;
+    assert_eq!(counter.load(Ordering::SeqCst), 5);
+
+    // Requests bypass the busy worker
+    let ids = Arc::new(AtomicUsize::new(0));
+    let mut pool = Pool::new(2, move || Worker {
+        id: ids.fetch_add(1, Ordering::SeqCst),
+    })
+    .with_routing(Routing::LeastLoaded)
+    .spawn();
+    pool.route(Hold)?;
+    for _ in 0..3 {
+        assert_eq!(pool.interact(WhoAmI).await?, 1);
+        // Lets the pool receive the report of the handled request
+        sleep(Duration::from_millis(10)).await;
+    }
+    pool.interrupt()?;
+    pool.join().await?;
+
+    // Workers that fail to start are not replaced endlessly
+    let starts = Arc::new(AtomicUsize::new(0));
+    let counter = starts.clone();
+    let restarts = Backoff::constant(Duration::from_millis(1)).max_attempts(2);
+    let mut pool = Pool::new(1, move || {
+        counter.fetch_add(1, Ordering::SeqCst);
+        Broken
+    })
+    .with_restarts(restarts)
+    .spawn();
+    sleep(Duration::from_millis(100)).await;
+    assert_eq!(starts.load(Ordering::SeqCst), 3);
+    pool.interrupt()?;
+    pool.join().await?;
+    Ok(())
+}
@@ -0,0 +1,109 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use crb::agent::{
+    Agent, AgentSession, Context, DoAsync, ManagedContext, Next, OnEvent, Standalone,
+};
+use crb::core::time::{timeout, Duration};
+use std::sync::{Arc, Mutex};
+
+type Journal = Arc<Mutex<Vec<String>>>;
+
+struct Peer {
+    journal: Journal,
+}
+
+impl Peer {
+    fn log(&self, record: String) {
+        self.journal.lock().unwrap().push(record);
+    }
+}
+
+impl Standalone for Peer {}
+
+impl Agent for Peer {
+    type Context = AgentSession<Self>;
+
+    fn begin(&mut self) -> Next<Self> {
+        Next::do_async(Handshake)
+    }
+}
+
+struct Handshake;
+
+#[async_trait]
+impl DoAsync<Handshake> for Peer {
+    async fn handle(&mut self, _: Handshake, ctx: &mut Context<Self>) -> Result<Next<Self>> {
+        let ack = ctx.receive_if(|ack: &HelloAck| ack.0 == 2).await;
+        self.log(format!("handshake {:?}", ack.map(|ack| ack.0)));
+        Ok(Next::do_async(Wait))
+    }
+}
+
+struct Wait;
+
+#[async_trait]
+impl DoAsync<Wait> for Peer {
+    async fn handle(&mut self, _: Wait, ctx: &mut Context<Self>) -> Result<Next<Self>> {
+        // Skipped messages stay in the session when the receiving is canceled
+        let ack = timeout(
+            Duration::from_millis(10),
+            ctx.receive_if(|ack: &HelloAck| ack.0 == 3),
+        )
+        .await;
+        self.log(format!(
+            "wait {}",
+            if ack.is_err() { "expired" } else { "done" }
+        ));
+        Ok(Next::events())
+    }
+}
+
+struct HelloAck(u32);
+
+#[async_trait]
+impl OnEvent<HelloAck> for Peer {
+    async fn handle(&mut self, event: HelloAck, _ctx: &mut Context<Self>) -> Result<()> {
+        self.log(format!("ack {}", event.0));
+        Ok(())
+    }
+}
+
+struct Data(u32);
+
+#[async_trait]
+impl OnEvent<Data> for Peer {
+    async fn handle(&mut self, event: Data, ctx: &mut Context<Self>) -> Result<()> {
+        self.log(format!("data {}", event.0));
+        if event.0 == 3 {
+            ctx.shutdown();
+        }
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn test_receive() -> Result<()> {
+    let journal = Journal::default();
+    let mut address = Peer {
+        journal: journal.clone(),
+    }
+    .spawn();
+    address.event(Data(1))?;
+    address.event(HelloAck(1))?;
+    address.event(Data(2))?;
+    address.event(HelloAck(2))?;
+    address.event(Data(3))?;
+    address.join().await?;
+    assert_eq!(
+        *journal.lock().unwrap(),
+        vec![
+            "handshake Some(2)",
+            "wait expired",
+            "data 1",
+            "ack 1",
+            "data 2",
+            "data 3"
+        ]
+    );
+    Ok(())
+}
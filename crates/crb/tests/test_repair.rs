@@ -0,0 +1,139 @@
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use crb::agent::{
+    Agent, AgentSession, Backoff, CircuitBreaker, CircuitChanged, CircuitState, Context, DoAsync,
+    ManagedContext, Next, OnEvent, Resilient, Standalone,
+};
+use crb::core::time::{timeout, Duration};
+use crb::runtime::{InterruptionLevel, Interruptor};
+use std::sync::{Arc, Mutex};
+
+type Journal = Arc<Mutex<Vec<CircuitState>>>;
+
+struct Client {
+    attempts: usize,
+    journal: Journal,
+}
+
+impl Standalone for Client {}
+
+impl Agent for Client {
+    type Context = AgentSession<Self>;
+
+    fn initialize(&mut self, ctx: &mut Context<Self>) -> Next<Self> {
+        let backoff = Backoff::exponential(Duration::from_millis(1)).with_jitter(0.5);
+        let mut breaker = CircuitBreaker::new(2, Duration::from_millis(10));
+        breaker.report_to(&*ctx);
+        let state = Resilient::new(Connect)
+            .with_backoff(backoff)
+            .with_breaker(breaker);
+        Next::do_async(state)
+    }
+}
+
+struct Connect;
+
+#[async_trait]
+impl DoAsync<Connect> for Client {
+    async fn repeat(&mut self, _: &mut Connect) -> Result<Option<Next<Self>>> {
+        self.attempts += 1;
+        if self.attempts <= 3 {
+            return Err(anyhow!("Connection refused"));
+        }
+        Ok(Some(Next::events()))
+    }
+}
+
+#[async_trait]
+impl OnEvent<CircuitChanged> for Client {
+    async fn handle(&mut self, event: CircuitChanged, ctx: &mut Context<Self>) -> Result<()> {
+        self.journal.lock().unwrap().push(event.to);
+        if event.to == CircuitState::Closed {
+            ctx.shutdown();
+        }
+        Ok(())
+    }
+}
+
+/// Never connects and waits for long delays.
+struct Offline {
+    with_breaker: bool,
+}
+
+impl Standalone for Offline {}
+
+impl Agent for Offline {
+    type Context = AgentSession<Self>;
+
+    fn begin(&mut self) -> Next<Self> {
+        let delay = Duration::from_secs(60);
+        let mut state = Resilient::new(Connect);
+        if self.with_breaker {
+            let backoff = Backoff::constant(Duration::from_millis(1));
+            state = state
+                .with_backoff(backoff)
+                .with_breaker(CircuitBreaker::new(1, delay));
+        } else {
+            state = state.with_backoff(Backoff::constant(delay));
+        }
+        Next::do_async(state)
+    }
+}
+
+#[async_trait]
+impl DoAsync<Connect> for Offline {
+    async fn repeat(&mut self, _: &mut Connect) -> Result<Option<Next<Self>>> {
+        Err(anyhow!("Connection refused"))
+    }
+}
+
+async fn stop_offline(with_breaker: bool) -> Result<()> {
+    let mut address = Offline { with_breaker }.spawn();
+    crb::core::time::sleep(Duration::from_millis(20)).await;
+    address.interrupt_with_level(InterruptionLevel::FLAG);
+    timeout(Duration::from_secs(5), address.join()).await??;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_repair() -> Result<()> {
+    let mut backoff = Backoff::exponential(Duration::from_millis(10)).max_attempts(3);
+    let delays: Vec<_> = (0..3)
+        .map(|_| backoff.next_delay(anyhow!("Failed")).unwrap())
+        .collect();
+    assert_eq!(delays, [10, 20, 40].map(Duration::from_millis));
+    assert!(backoff.next_delay(anyhow!("Failed")).is_err());
+
+    let mut backoff = Backoff::constant(Duration::from_millis(10))
+        .with_jitter(0.5)
+        .retry_if(|err| err.to_string() != "Fatal");
+    let delay = backoff.next_delay(anyhow!("Failed"))?;
+    assert!(delay <= Duration::from_millis(10) && delay >= Duration::from_millis(5));
+    assert!(backoff.next_delay(anyhow!("Fatal")).is_err());
+
+    let max_delay = Duration::from_secs(1);
+    let mut backoff = Backoff::exponential(Duration::from_millis(100)).with_max_delay(max_delay);
+    let last = (0..200)
+        .map(|_| backoff.next_delay(anyhow!("Failed")).unwrap())
+        .last();
+    assert_eq!(last, Some(max_delay));
+    assert_eq!(backoff.attempts(), 200);
+
+    let journal = Journal::default();
+    let client = Client {
+        attempts: 0,
+        journal: journal.clone(),
+    };
+    let mut address = client.spawn();
+    address.join().await?;
+    use CircuitState::*;
+    assert_eq!(
+        *journal.lock().unwrap(),
+        vec![Open, HalfOpen, Open, HalfOpen, Closed]
+    );
+
+    // Delays end when the agent is stopped
+    stop_offline(false).await?;
+    stop_offline(true).await?;
+    Ok(())
+}
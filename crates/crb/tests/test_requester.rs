@@ -0,0 +1,93 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use crb::agent::{Agent, AgentSession, Context, ManagedContext, Next, Standalone};
+use crb::superagent::{
+    OnRequest, OnResponse, Output, Request, Requester, Supervisor, SupervisorSession, ToRequester,
+};
+use std::sync::{Arc, Mutex};
+
+struct Calculator;
+
+impl Standalone for Calculator {}
+
+impl Agent for Calculator {
+    type Context = AgentSession<Self>;
+}
+
+struct Add(u32, u32);
+
+impl Request for Add {
+    type Response = u32;
+}
+
+#[async_trait]
+impl OnRequest<Add> for Calculator {
+    async fn on_request(&mut self, request: Add, _ctx: &mut Context<Self>) -> Result<u32> {
+        Ok(request.0 + request.1)
+    }
+}
+
+struct Double(u32);
+
+impl Request for Double {
+    type Response = String;
+}
+
+struct Client {
+    adder: Requester<Add>,
+    result: Arc<Mutex<Option<u32>>>,
+}
+
+impl Standalone for Client {}
+
+impl Supervisor for Client {
+    type BasedOn = AgentSession<Self>;
+    type GroupBy = ();
+}
+
+impl Agent for Client {
+    type Context = SupervisorSession<Self>;
+
+    fn initialize(&mut self, ctx: &mut Context<Self>) -> Next<Self> {
+        ctx.assign(self.adder.request(Add(2, 3)), (), ());
+        Next::events()
+    }
+}
+
+#[async_trait]
+impl OnResponse<u32> for Client {
+    async fn on_response(
+        &mut self,
+        response: Output<u32>,
+        _tag: (),
+        ctx: &mut Context<Self>,
+    ) -> Result<()> {
+        *self.result.lock().unwrap() = Some(response?);
+        ctx.shutdown();
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn test_requester() -> Result<()> {
+    let mut calculator = Calculator.spawn();
+    let adder: Requester<Add> = calculator.requester();
+    assert_eq!(adder.request(Add(1, 2)).await?, 3);
+
+    let doubler = adder.reform(|Double(value)| Add(value, value), |sum| sum.to_string());
+    assert_eq!(doubler.request(Double(21)).await?, "42");
+
+    let result = Arc::new(Mutex::new(None));
+    let mock = Requester::mock(|Add(a, b)| Ok(a * b));
+    let mut client = Client {
+        adder: mock,
+        result: result.clone(),
+    }
+    .spawn();
+    client.join().await?;
+    assert_eq!(*result.lock().unwrap(), Some(6));
+
+    calculator.interrupt()?;
+    calculator.join().await?;
+    Ok(())
+}
@@ -0,0 +1,58 @@
+#![cfg(unix)]
+
+use anyhow::Result;
+use async_trait::async_trait;
+use crb::agent::{Agent, AgentSession, DoAsync, Next, Standalone};
+use crb::core::time::{sleep, Duration};
+use crb::runtime::InterruptionLevel;
+use crb_system::{Exit, Main, Shutdown};
+use std::process::Command;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+/// Doesn't read the mailbox, so only the `FLAG` level stops it.
+struct Busy;
+
+impl Standalone for Busy {}
+
+impl Agent for Busy {
+    type Context = AgentSession<Self>;
+
+    fn begin(&mut self) -> Next<Self> {
+        Next::do_async(())
+    }
+}
+
+#[async_trait]
+impl DoAsync for Busy {
+    async fn repeat(&mut self, _: &mut ()) -> Result<Option<Next<Self>>> {
+        sleep(Duration::from_millis(1)).await;
+        Ok(None)
+    }
+}
+
+#[tokio::test]
+async fn test_shutdown() -> Result<()> {
+    let flushed = Arc::new(AtomicBool::new(false));
+    let hook_flushed = flushed.clone();
+    let shutdown = Shutdown::new()
+        .with_deadline(InterruptionLevel::EVENT, Duration::from_millis(10))
+        .on_shutdown(move || async move {
+            hook_flushed.store(true, Ordering::SeqCst);
+            Ok(())
+        });
+    let address = Busy.spawn();
+    let mut exit = address.join_or_shutdown(shutdown);
+    // Signal handlers are installed on the first poll
+    assert!(futures::poll!(&mut exit).is_pending());
+
+    let pid = std::process::id().to_string();
+    let status = Command::new("kill").args(["-TERM", &pid]).status()?;
+    assert!(status.success());
+
+    let exit = exit.await?;
+    assert_eq!(exit, Exit::Escalated);
+    assert_eq!(exit.code(), 1);
+    assert!(flushed.load(Ordering::SeqCst));
+    Ok(())
+}
@@ -0,0 +1,71 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use crb::agent::{Agent, AgentContext, AgentSession, Context, Next, OnEvent, Standalone};
+use crb::send::Headers;
+use crb::superagent::{InteractExt, Interaction, OnRequest, Request};
+
+struct Cache {
+    value: Option<u32>,
+}
+
+impl Standalone for Cache {}
+
+impl Agent for Cache {
+    type Context = AgentSession<Self>;
+
+    fn initialize(&mut self, ctx: &mut Context<Self>) -> Next<Self> {
+        ctx.session().stash.set_limit(2);
+        Next::events()
+    }
+}
+
+struct Loaded(u32);
+
+#[async_trait]
+impl OnEvent<Loaded> for Cache {
+    async fn handle(&mut self, event: Loaded, ctx: &mut Context<Self>) -> Result<()> {
+        self.value = Some(event.0);
+        ctx.unstash_all();
+        Ok(())
+    }
+}
+
+struct Get;
+
+impl Request for Get {
+    type Response = (u32, Option<String>);
+}
+
+#[async_trait]
+impl OnRequest<Get> for Cache {
+    async fn handle(&mut self, msg: Interaction<Get>, ctx: &mut Context<Self>) -> Result<()> {
+        match self.value {
+            Some(value) => {
+                let headers = ctx.headers();
+                let correlation_id = headers.and_then(|headers| headers.correlation_id.clone());
+                msg.interplay.responder.send((value, correlation_id))
+            }
+            None => Ok(ctx.stash(msg)?),
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_stash() -> Result<()> {
+    let mut address = Cache { value: None }.spawn();
+    let first = address.interact_with(Get, Headers::new().with_correlation_id("first"));
+    let second = address.interact(Get);
+    let overflowed = address.interact(Get);
+    address.event(Loaded(42))?;
+    let last = address.interact(Get);
+
+    // Headers are kept with stashed messages
+    assert_eq!(first.await?, (42, Some("first".into())));
+    assert_eq!(second.await?, (42, None));
+    assert!(overflowed.await.is_err());
+    assert_eq!(last.await?, (42, None));
+
+    address.interrupt()?;
+    address.join().await?;
+    Ok(())
+}
@@ -0,0 +1,132 @@
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use crb::agent::{Address, Agent, AgentSession, Context, ManagedContext, Next, Standalone};
+use crb::superagent::{
+    OnStreamRequest, OnStreamResponse, StreamInteractExt, StreamRequest, StreamResponder,
+    Supervisor, SupervisorSession,
+};
+use futures::StreamExt;
+use std::sync::{Arc, Mutex};
+
+struct Storage;
+
+impl Standalone for Storage {}
+
+impl Agent for Storage {
+    type Context = AgentSession<Self>;
+}
+
+struct Pages {
+    total: u32,
+    broken: bool,
+}
+
+impl StreamRequest for Pages {
+    type Item = u32;
+}
+
+#[async_trait]
+impl OnStreamRequest<Pages> for Storage {
+    async fn on_stream_request(
+        &mut self,
+        request: Pages,
+        responder: StreamResponder<u32>,
+        _ctx: &mut Context<Self>,
+    ) -> Result<()> {
+        for page in 0..request.total {
+            responder.send(page)?;
+        }
+        if request.broken {
+            return Err(anyhow!("Broken page"));
+        }
+        responder.end()
+    }
+}
+
+type Journal = Arc<Mutex<Vec<String>>>;
+
+struct Reader {
+    storage: Address<Storage>,
+    journal: Journal,
+}
+
+impl Standalone for Reader {}
+
+impl Supervisor for Reader {
+    type BasedOn = AgentSession<Self>;
+    type GroupBy = ();
+}
+
+impl Agent for Reader {
+    type Context = SupervisorSession<Self>;
+
+    fn initialize(&mut self, ctx: &mut Context<Self>) -> Next<Self> {
+        let pages = Pages {
+            total: 2,
+            broken: false,
+        };
+        let stream = self.storage.interact_stream(pages);
+        ctx.assign(stream, (), ());
+        Next::events()
+    }
+}
+
+#[async_trait]
+impl OnStreamResponse<u32> for Reader {
+    async fn on_item(&mut self, item: u32, _tag: (), _ctx: &mut Context<Self>) -> Result<()> {
+        self.journal.lock().unwrap().push(format!("page {item}"));
+        Ok(())
+    }
+
+    async fn on_end(
+        &mut self,
+        result: Result<()>,
+        _tag: (),
+        ctx: &mut Context<Self>,
+    ) -> Result<()> {
+        self.journal
+            .lock()
+            .unwrap()
+            .push(format!("end {}", result.is_ok()));
+        ctx.shutdown();
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn test_stream_response() -> Result<()> {
+    let mut storage = Storage.spawn();
+
+    let pages = Pages {
+        total: 3,
+        broken: false,
+    };
+    let items: Vec<_> = storage.interact_stream(pages).collect().await;
+    let items: Vec<u32> = items.into_iter().collect::<Result<_>>()?;
+    assert_eq!(items, vec![0, 1, 2]);
+
+    let pages = Pages {
+        total: 1,
+        broken: true,
+    };
+    let mut stream = storage.interact_stream(pages);
+    assert_eq!(stream.next().await.transpose()?, Some(0));
+    assert!(stream.next().await.unwrap().is_err());
+    assert!(stream.next().await.is_none());
+
+    let journal = Journal::default();
+    let mut reader = Reader {
+        storage: storage.clone(),
+        journal: journal.clone(),
+    }
+    .spawn();
+    reader.join().await?;
+    assert_eq!(
+        *journal.lock().unwrap(),
+        vec!["page 0", "page 1", "end true"]
+    );
+
+    storage.interrupt()?;
+    storage.join().await?;
+    Ok(())
+}
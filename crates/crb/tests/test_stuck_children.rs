@@ -0,0 +1,88 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use crb::agent::{Agent, AgentSession, Context, ManagedContext, Next, Standalone};
+use crb::core::time::{timeout, Duration};
+use crb::runtime::{InterruptionLevel, Interruptor, Runtime};
+use crb::superagent::{GroupPolicy, Relation, Supervisor, SupervisorContext, SupervisorSession};
+use std::future::pending;
+use std::sync::{Arc, Mutex};
+
+type Log = Arc<Mutex<Vec<&'static str>>>;
+
+struct Deaf;
+
+impl Interruptor for Deaf {
+    fn interrupt(&self) {}
+}
+
+// Never stops whatever the level is
+struct Stuck;
+
+#[async_trait]
+impl Runtime for Stuck {
+    fn get_interruptor(&mut self) -> Box<dyn Interruptor> {
+        Box::new(Deaf)
+    }
+
+    async fn routine(&mut self) {
+        pending::<()>().await;
+    }
+}
+
+struct Stubborn;
+
+impl Agent for Stubborn {
+    type Context = AgentSession<Self>;
+
+    // Ignores events, but not flags
+    fn interrupt(&mut self, _ctx: &mut Context<Self>) {}
+}
+
+struct Boss {
+    log: Log,
+}
+
+impl Standalone for Boss {}
+
+impl Supervisor for Boss {
+    type BasedOn = AgentSession<Self>;
+    /// Only the `true` group is escalated.
+    type GroupBy = bool;
+
+    fn finished(&mut self, _rel: &Relation<Self>, _ctx: &mut Context<Self>) {
+        self.log.lock().unwrap().push("finished");
+    }
+
+    fn stuck(&mut self, _rel: &Relation<Self>, _ctx: &mut Context<Self>) {
+        self.log.lock().unwrap().push("stuck");
+    }
+}
+
+impl Agent for Boss {
+    type Context = SupervisorSession<Self>;
+
+    fn initialize(&mut self, ctx: &mut Context<Self>) -> Next<Self> {
+        let tracker = ctx.tracker();
+        tracker.set_grace_period(Duration::from_millis(20));
+        let policy = GroupPolicy::new()
+            .with_level(InterruptionLevel::EVENT)
+            .without_escalation();
+        tracker.set_policy(false, policy);
+        ctx.spawn_trackable(Stuck, true);
+        ctx.spawn_agent(Stubborn, true);
+        // Is abandoned after the grace period
+        ctx.spawn_agent(Stubborn, false);
+        ctx.shutdown();
+        Next::events()
+    }
+}
+
+#[tokio::test]
+async fn test_stuck_children() -> Result<()> {
+    let log = Log::default();
+    let mut boss = Boss { log: log.clone() }.spawn();
+    timeout(Duration::from_secs(5), boss.join()).await??;
+    let log = log.lock().unwrap().clone();
+    assert_eq!(log, ["finished", "stuck", "stuck"]);
+    Ok(())
+}
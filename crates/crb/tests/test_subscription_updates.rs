@@ -0,0 +1,98 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use crb::agent::{Agent, AgentSession, Context, OnEvent, Standalone};
+use crb::core::Unique;
+use crb::superagent::{
+    InteractExt, ManageSubscription, OnRequest, Request, SubscribeExt, SubscribersExt, Subscription,
+};
+use futures::StreamExt;
+
+struct Counter {
+    value: u32,
+}
+
+impl Standalone for Counter {}
+
+impl Agent for Counter {
+    type Context = AgentSession<Self>;
+}
+
+/// Updates are sent when the value reaches the threshold.
+struct Watch {
+    threshold: u32,
+}
+
+impl Subscription for Watch {
+    type State = u32;
+    type Update = u32;
+}
+
+#[async_trait]
+impl ManageSubscription<Watch> for Counter {
+    async fn subscribe(&mut self, _sub_id: Unique<Watch>, _ctx: &mut Context<Self>) -> Result<u32> {
+        Ok(self.value)
+    }
+
+    async fn unsubscribe(
+        &mut self,
+        _sub_id: Unique<Watch>,
+        _ctx: &mut Context<Self>,
+    ) -> Result<()> {
+        Ok(())
+    }
+}
+
+struct Increment;
+
+#[async_trait]
+impl OnEvent<Increment> for Counter {
+    async fn handle(&mut self, _event: Increment, ctx: &mut Context<Self>) -> Result<()> {
+        self.value += 1;
+        let value = self.value;
+        ctx.subscribers::<Watch>()
+            .broadcast_with(|watch| (value >= watch.threshold).then_some(value));
+        Ok(())
+    }
+}
+
+struct Watchers;
+
+impl Request for Watchers {
+    type Response = usize;
+}
+
+#[async_trait]
+impl OnRequest<Watchers> for Counter {
+    async fn on_request(&mut self, _: Watchers, ctx: &mut Context<Self>) -> Result<usize> {
+        Ok(ctx.subscribers::<Watch>().len())
+    }
+}
+
+#[tokio::test]
+async fn test_subscription_updates() -> Result<()> {
+    let mut address = Counter { value: 0 }.spawn();
+
+    let all = address.subscribe(Watch { threshold: 0 }).await?;
+    let high = address.subscribe(Watch { threshold: 2 }).await?;
+    assert_eq!(all.state, 0);
+    let mut all = all.entry;
+    let mut high = high.entry;
+    assert_eq!(address.interact(Watchers).await?, 2);
+
+    for _ in 0..3 {
+        address.event(Increment)?;
+    }
+    assert_eq!(all.next().await, Some(1));
+    assert_eq!(all.next().await, Some(2));
+    assert_eq!(all.next().await, Some(3));
+    assert_eq!(high.next().await, Some(2));
+    assert_eq!(high.next().await, Some(3));
+
+    drop(high);
+    assert_eq!(address.interact(Watchers).await?, 1);
+
+    address.interrupt()?;
+    address.join().await?;
+    assert_eq!(all.next().await, None);
+    Ok(())
+}